@@ -1,10 +1,13 @@
-use std::fmt::{Debug, Display};
+use std::{
+    collections::BTreeMap,
+    fmt::{Debug, Display},
+};
 
 use bson::oid::ObjectId;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::domain::models::user_name::UserName;
+use crate::domain::models::{reaction::ReactionKind, user_name::UserName};
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Article {
     #[serde(rename = "_id")]
@@ -12,22 +15,118 @@ pub struct Article {
     pub author: UserName,
     pub content: String,
     pub title: String,
+    /// URLに使用するスラッグ　タイトルから作られ、著者ごとに一意になる（`slug::slugify`を参照）
+    /// スラッグが導入される前の記事は、起動時に割り当てられるまで空になる
+    #[serde(default)]
+    pub slug: String,
+    /// これまでに使用したすべてのスラッグ（現在のスラッグを含む）
+    /// 古いスラッグでアクセスされた場合は、現在のスラッグに転送する
+    #[serde(default)]
+    pub slug_history: Vec<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub visibility: Visibility,
+    /// 正規化されたタグ（`tag::normalize_tags`を参照）
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// 予約投稿の日時　下書きにのみ設定でき、この日時を過ぎると自動的に公開される
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publish_at: Option<DateTime<Utc>>,
+    /// 最新の版番号　版の記録が導入される前の記事は、更新されるまで`0`になる
+    #[serde(default)]
+    pub revision: u32,
+    /// 楽観的排他制御に使用するバージョン　記事が変更されるたびに1増える
+    /// バージョンが導入される前の記事は、更新されるまで`0`になる
+    #[serde(default)]
+    pub version: u32,
+    /// リアクションの種類ごとの数　1つもない種類は含まない
+    #[serde(default)]
+    pub reactions: BTreeMap<ReactionKind, u64>,
+    /// 最後にリアクションが付けられた、または取り消された日時
+    /// リアクションは記事の内容ではないため、`updated_at`や`version`は変更しない
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reacted_at: Option<DateTime<Utc>>,
 }
 
 impl Article {
-    pub fn new_article(title: String, author: UserName, content: String) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_article(
+        id: ArticleId,
+        title: String,
+        slug: String,
+        author: UserName,
+        content: String,
+        visibility: Visibility,
+        publish_at: Option<DateTime<Utc>>,
+        tags: Vec<String>,
+    ) -> Self {
         let now = Utc::now();
         Article {
-            id: ArticleId::new(),
+            id,
             title,
+            slug_history: vec![slug.clone()],
+            slug,
             author,
             content,
             created_at: now,
             updated_at: now,
+            visibility,
+            tags,
+            publish_at,
+            revision: 1,
+            version: 1,
+            reactions: BTreeMap::new(),
+            reacted_at: None,
         }
     }
+
+    /// レスポンスの表現が最後に変化した日時　リアクションの変化も含める
+    pub fn last_modified(&self) -> DateTime<Utc> {
+        self.reacted_at.map_or(self.updated_at, |reacted_at| {
+            reacted_at.max(self.updated_at)
+        })
+    }
+
+    /// 予約投稿の日時を過ぎた下書きかどうか
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.visibility == Visibility::Draft && self.publish_at.is_some_and(|at| at <= now)
+    }
+
+    /// `viewer`がIDを指定してこの記事を閲覧できるかどうか
+    /// `viewer`が`None`の場合は未ログインのユーザーとして扱う
+    pub fn is_viewable_by(&self, viewer: Option<&UserName>) -> bool {
+        match self.visibility {
+            Visibility::Published | Visibility::Unlisted => true,
+            Visibility::Draft | Visibility::Private => viewer == Some(&self.author),
+        }
+    }
+
+    /// `viewer`に対して記事の一覧や検索結果にこの記事を含めるかどうか
+    /// 公開記事に加えて、自分の記事は下書き以外を含める
+    pub fn is_listed_for(&self, viewer: Option<&UserName>) -> bool {
+        match self.visibility {
+            Visibility::Published => true,
+            Visibility::Private | Visibility::Unlisted => viewer == Some(&self.author),
+            Visibility::Draft => false,
+        }
+    }
+}
+
+/// 記事の公開範囲
+/// 公開範囲が導入される前に作成された記事はフィールドを持たないため、デフォルトは公開とする
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Visibility {
+    /// 下書き　著者のみが閲覧でき、`/api/users/{user_name}/drafts`で一覧を取得できる
+    Draft,
+    /// 公開　誰でも閲覧でき、一覧や検索結果にも表示される
+    #[default]
+    Published,
+    /// 限定公開　URLを知っていれば誰でも閲覧できるが、他のユーザーの一覧や検索結果には表示されない
+    Unlisted,
+    /// 非公開　著者のみが閲覧できる
+    Private,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Default, Hash)]
@@ -46,6 +145,12 @@ impl ArticleId {
     }
 }
 
+impl From<ArticleId> for ObjectId {
+    fn from(id: ArticleId) -> Self {
+        id.inner
+    }
+}
+
 impl Debug for ArticleId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_tuple("ArticleId")
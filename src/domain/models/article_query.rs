@@ -1,5 +1,40 @@
-#[derive(Debug, Clone)]
+use serde::{Deserialize, Serialize};
+
+use super::title_regex::TitleRegex;
+
+#[derive(Debug, Clone, Default)]
 pub struct ArticleQuery {
+    /// タイトルに含まれる文字列　大文字・小文字を区別せず、正規表現としては扱わない
     pub title: Option<String>,
+    /// タイトルが一致する正規表現
+    pub title_regex: Option<TitleRegex>,
     pub author: Option<String>,
+    /// すべてのタグが付いた記事に絞り込む（正規化済みのタグ）
+    pub tags: Vec<String>,
+    /// いずれかのタグが付いた記事に絞り込む（正規化済みのタグ）　空の場合は絞り込まない
+    pub any_tags: Vec<String>,
+    pub sort: ArticleSort,
+}
+
+/// 記事の一覧の並び順
+/// どの並び順でも、値が同じ記事はIDの順に並べて順序を一意に決める
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArticleSort {
+    /// 作成日時の新しい順
+    Newest,
+    /// 作成日時の古い順
+    #[default]
+    Oldest,
+    /// 更新日時の新しい順
+    Updated,
+    /// タイトルの昇順（文字コード順）
+    Title,
+}
+
+impl ArticleSort {
+    /// 作成日時とIDによるカーソル（`PageCursor`）で続きを取得できる並び順かどうか
+    pub fn supports_cursor(self) -> bool {
+        matches!(self, ArticleSort::Newest | ArticleSort::Oldest)
+    }
 }
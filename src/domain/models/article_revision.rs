@@ -0,0 +1,79 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+
+use crate::domain::models::article::{Article, ArticleId};
+
+/// 記事の版
+/// 記事の作成時と、タイトルか本文が更新されるたびに追記され、変更や削除はされない
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ArticleRevision {
+    pub article_id: ArticleId,
+    /// 記事ごとに1から始まる版番号
+    pub rev: u32,
+    pub title: String,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ArticleRevision {
+    /// 記事の現在の内容から版を作成する
+    pub fn from_article(article: &Article) -> Self {
+        ArticleRevision {
+            article_id: article.id,
+            rev: article.revision,
+            title: article.title.clone(),
+            content: article.content.clone(),
+            created_at: article.updated_at,
+        }
+    }
+}
+
+/// 2つの版のタイトルと本文の行単位の差分
+#[derive(Serialize, Debug, Clone)]
+pub struct RevisionDiff {
+    pub from: u32,
+    pub to: u32,
+    pub title: Vec<DiffLine>,
+    pub content: Vec<DiffLine>,
+}
+
+impl RevisionDiff {
+    pub fn between(from: &ArticleRevision, to: &ArticleRevision) -> Self {
+        RevisionDiff {
+            from: from.rev,
+            to: to.rev,
+            title: diff_lines(&from.title, &to.title),
+            content: diff_lines(&from.content, &to.content),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine {
+    pub op: DiffOp,
+    /// 改行を除いた行の内容
+    pub text: String,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffOp {
+    Equal,
+    Insert,
+    Delete,
+}
+
+fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    TextDiff::from_lines(old, new)
+        .iter_all_changes()
+        .map(|change| DiffLine {
+            op: match change.tag() {
+                ChangeTag::Equal => DiffOp::Equal,
+                ChangeTag::Insert => DiffOp::Insert,
+                ChangeTag::Delete => DiffOp::Delete,
+            },
+            text: change.value().trim_end_matches(['\n', '\r']).to_string(),
+        })
+        .collect()
+}
@@ -1,35 +1,166 @@
+use std::{collections::HashMap, sync::Arc};
+
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 
-use crate::domain::models::article::ArticleId;
+use crate::domain::models::{
+    article::{ArticleId, Visibility},
+    article_revision::{ArticleRevision, RevisionDiff},
+    author_stats::AuthorArticleStats,
+    page_cursor::PageCursor,
+    reaction::ReactionKind,
+    search::SearchResults,
+    tag::TagCount,
+};
 
-use super::{article::Article, article_query::ArticleQuery, user_name::UserName};
+use super::{
+    article::Article,
+    article_query::{ArticleQuery, ArticleSort},
+    user_name::UserName,
+};
 #[async_trait]
 pub trait ArticleService {
     async fn get_articles(
         &self,
         skip: usize,
         limit: usize,
+        sort: ArticleSort,
+        cursor: Option<PageCursor>,
+        viewer: Option<&UserName>,
     ) -> Result<Vec<Article>, ArticleServiceError>;
     async fn get_article_by_id(&self, id: ArticleId) -> Result<Article, ArticleServiceError>;
+    /// 著者と現在または過去のスラッグから記事を取得する
+    async fn get_article_by_slug(
+        &self,
+        author: &str,
+        slug: &str,
+    ) -> Result<Article, ArticleServiceError>;
     async fn create_article(
         &self,
         title: String,
         author: UserName,
         content: String,
+        visibility: Visibility,
+        publish_at: Option<DateTime<Utc>>,
+        tags: Vec<String>,
     ) -> Result<Article, ArticleServiceError>;
+    #[allow(clippy::too_many_arguments)]
     async fn update_article(
         &self,
         id: ArticleId,
         title: Option<String>,
         content: Option<String>,
+        visibility: Option<Visibility>,
+        publish_at: Option<Option<DateTime<Utc>>>,
+        tags: Option<Vec<String>>,
+        expected_version: Option<u32>,
     ) -> Result<Article, ArticleServiceError>;
-    async fn delete_article(&self, id: ArticleId) -> Result<(), ArticleServiceError>;
+    async fn delete_article(
+        &self,
+        id: ArticleId,
+        expected_version: Option<u32>,
+    ) -> Result<(), ArticleServiceError>;
     async fn search_articles(
         &self,
         skip: usize,
         limit: usize,
         query: ArticleQuery,
+        viewer: Option<&UserName>,
+    ) -> Result<Vec<Article>, ArticleServiceError>;
+    /// タイトルと本文を全文検索し、関連度の高い順に返す
+    /// `q`: 検索クエリ, `query`: 検索結果をさらに絞り込む条件（並び順は無視する）
+    async fn search_full_text(
+        &self,
+        q: &str,
+        query: ArticleQuery,
+        skip: usize,
+        limit: usize,
+        viewer: Option<&UserName>,
+    ) -> Result<SearchResults, ArticleServiceError>;
+    async fn count_articles(&self, viewer: Option<&UserName>) -> Result<u64, ArticleServiceError>;
+    /// 著者の記事のうち`viewer`の一覧に表示されるものを取得する
+    async fn get_articles_by_author(
+        &self,
+        author: &str,
+        skip: usize,
+        limit: usize,
+        sort: ArticleSort,
+        cursor: Option<PageCursor>,
+        viewer: Option<&UserName>,
     ) -> Result<Vec<Article>, ArticleServiceError>;
+    async fn count_articles_by_author(
+        &self,
+        author: &str,
+        viewer: Option<&UserName>,
+    ) -> Result<u64, ArticleServiceError>;
+    /// 著者の記事のうち`viewer`の一覧に表示されるものを集計する
+    async fn get_author_stats(
+        &self,
+        author: &str,
+        viewer: Option<&UserName>,
+    ) -> Result<AuthorArticleStats, ArticleServiceError>;
+    async fn count_search_articles(
+        &self,
+        query: ArticleQuery,
+        viewer: Option<&UserName>,
+    ) -> Result<u64, ArticleServiceError>;
+    async fn get_drafts(
+        &self,
+        author: &UserName,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Vec<Article>, ArticleServiceError>;
+    async fn publish_due_articles(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Article>, ArticleServiceError>;
+    async fn get_revisions(
+        &self,
+        id: ArticleId,
+    ) -> Result<Vec<ArticleRevision>, ArticleServiceError>;
+    async fn get_revision(
+        &self,
+        id: ArticleId,
+        rev: u32,
+    ) -> Result<ArticleRevision, ArticleServiceError>;
+    async fn diff_revisions(
+        &self,
+        id: ArticleId,
+        from: u32,
+        to: u32,
+    ) -> Result<RevisionDiff, ArticleServiceError>;
+    async fn restore_revision(
+        &self,
+        id: ArticleId,
+        rev: u32,
+    ) -> Result<Article, ArticleServiceError>;
+    async fn get_tag_counts(&self) -> Result<Vec<TagCount>, ArticleServiceError>;
+    /// スラッグが導入される前に作成された記事に、タイトルからスラッグを割り当てる
+    /// 割り当てた記事の数を返す
+    async fn assign_missing_slugs(&self) -> Result<usize, ArticleServiceError>;
+    /// リアクションを付ける　既に同じ種類のリアクションを付けている場合は何もしない
+    async fn add_reaction(
+        &self,
+        id: ArticleId,
+        user: UserName,
+        kind: ReactionKind,
+    ) -> Result<Article, ArticleServiceError>;
+    /// リアクションを取り消す　同じ種類のリアクションを付けていない場合は何もしない
+    async fn remove_reaction(
+        &self,
+        id: ArticleId,
+        user: &UserName,
+        kind: ReactionKind,
+    ) -> Result<Article, ArticleServiceError>;
+    /// `user`が各記事に付けたリアクションを取得する
+    async fn get_user_reactions(
+        &self,
+        ids: &[ArticleId],
+        user: &UserName,
+    ) -> Result<HashMap<ArticleId, Vec<ReactionKind>>, ArticleServiceError>;
+    /// 記事の本文をMarkdownとしてサニタイズされたHTMLに変換する
+    /// 変換結果は`updated_at`ごとにキャッシュされる
+    fn render_content_html(&self, article: &Article) -> Arc<str>;
 }
 
 #[derive(Debug, Clone, thiserror::Error)]
@@ -38,6 +169,14 @@ pub enum ArticleServiceError {
     ArticleNotFound,
     #[error("Article already exists")]
     ArticleAlreadyExists,
+    #[error("Article has been modified")]
+    VersionMismatch,
+    #[error("Revision not found")]
+    RevisionNotFound,
+    #[error("publish_at can only be set on drafts")]
+    InvalidSchedule,
+    #[error("Search took too long; simplify title_regex")]
+    SearchTimeout,
     #[error("Database error")]
     DatabaseError(#[from] mongodb::error::Error),
 }
@@ -0,0 +1,41 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::article::ArticleId;
+
+/// 著者の記事を集計した結果
+/// コメントは記事とは別に保存されるため、コメントを数える対象の記事のIDも含める
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuthorArticleStats {
+    pub article_count: u64,
+    /// 最も古い記事の作成日時　記事がない場合は`None`
+    pub first_posted_at: Option<DateTime<Utc>>,
+    /// 最も新しい記事の作成日時　記事がない場合は`None`
+    pub last_posted_at: Option<DateTime<Utc>>,
+    /// すべての記事に付いたリアクションの数の合計
+    pub total_reactions: u64,
+    pub article_ids: Vec<ArticleId>,
+}
+
+/// ユーザーのプロフィールに表示する、執筆についての統計
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct AuthorStats {
+    pub article_count: u64,
+    pub first_posted_at: Option<DateTime<Utc>>,
+    pub last_posted_at: Option<DateTime<Utc>>,
+    pub total_reactions: u64,
+    /// すべての記事に付いた、承認済みで削除されていないコメントの数の合計
+    pub total_comments: u64,
+}
+
+impl AuthorStats {
+    pub fn new(articles: AuthorArticleStats, total_comments: u64) -> Self {
+        AuthorStats {
+            article_count: articles.article_count,
+            first_posted_at: articles.first_posted_at,
+            last_posted_at: articles.last_posted_at,
+            total_reactions: articles.total_reactions,
+            total_comments,
+        }
+    }
+}
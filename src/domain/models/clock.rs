@@ -0,0 +1,17 @@
+use chrono::{DateTime, Utc};
+
+/// 現在時刻を取得する操作を抽象化したトレイト
+/// テストで時刻を自由に進められるようにするために使用する
+pub trait Clock: Clone + Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// システムの時計をそのまま使用する`Clock`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
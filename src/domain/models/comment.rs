@@ -0,0 +1,134 @@
+use std::fmt::{Debug, Display};
+
+use bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::models::{article::ArticleId, user_name::UserName};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Comment {
+    #[serde(rename = "_id")]
+    pub id: CommentId,
+    pub article_id: ArticleId,
+    /// コメントされた記事の著者　モデレーションの対象を絞り込むために保持する
+    /// モデレーションが導入される前のコメントは`None`になる
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub article_author: Option<UserName>,
+    /// 返信先のコメント　記事への直接のコメントの場合は`None`
+    pub parent_id: Option<CommentId>,
+    pub author: UserName,
+    /// 削除されたコメントの場合は空になる
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// 返信のスレッドを保つため、コメントは論理削除する
+    #[serde(default)]
+    pub deleted: bool,
+    #[serde(default)]
+    pub status: CommentStatus,
+}
+
+impl Comment {
+    pub fn new_comment(
+        article_id: ArticleId,
+        article_author: UserName,
+        parent_id: Option<CommentId>,
+        author: UserName,
+        content: String,
+        status: CommentStatus,
+    ) -> Self {
+        let now = Utc::now();
+        Comment {
+            id: CommentId::new(),
+            article_id,
+            article_author: Some(article_author),
+            parent_id,
+            author,
+            content,
+            created_at: now,
+            updated_at: now,
+            deleted: false,
+            status,
+        }
+    }
+
+    /// `viewer`がこのコメントを閲覧できるかどうか
+    /// 承認待ちのコメントは記事の著者とコメントの著者のみ、却下されたコメントはコメントの著者のみが閲覧できる
+    pub fn is_visible_to(&self, viewer: Option<&UserName>, article_author: &UserName) -> bool {
+        match self.status {
+            CommentStatus::Approved => true,
+            CommentStatus::Pending => {
+                viewer.is_some_and(|viewer| viewer == &self.author || viewer == article_author)
+            }
+            CommentStatus::Rejected => viewer == Some(&self.author),
+        }
+    }
+}
+
+/// コメントの承認状態
+/// モデレーションが導入される前のコメントはフィールドを持たないため、デフォルトは承認済みとする
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommentStatus {
+    #[default]
+    Approved,
+    /// 記事の著者の承認を待っている
+    Pending,
+    Rejected,
+}
+
+/// 返信を含むコメントのスレッド
+#[derive(Serialize, Debug, Clone)]
+pub struct CommentThread {
+    #[serde(flatten)]
+    pub comment: Comment,
+    pub replies: Vec<CommentThread>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Hash)]
+pub struct CommentId {
+    inner: ObjectId,
+}
+
+impl CommentId {
+    pub fn new() -> Self {
+        Self {
+            inner: ObjectId::new(),
+        }
+    }
+    pub fn parse_str(s: &str) -> Result<Self, bson::oid::Error> {
+        ObjectId::parse_str(s).map(|inner| CommentId { inner })
+    }
+}
+
+impl Debug for CommentId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("CommentId")
+            .field(&self.inner.to_hex())
+            .finish()
+    }
+}
+impl Display for CommentId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.inner.to_hex())
+    }
+}
+
+impl Serialize for CommentId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.inner.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for CommentId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        ObjectId::deserialize(deserializer).map(|inner| Self { inner })
+    }
+}
@@ -0,0 +1,73 @@
+use async_trait::async_trait;
+
+use super::{
+    article::ArticleId,
+    comment::{Comment, CommentId, CommentStatus, CommentThread},
+    user::CommentModeration,
+    user_name::UserName,
+};
+
+#[async_trait]
+pub trait CommentService {
+    /// 記事のコメントのうち`viewer`が閲覧できるものを、返信のスレッドとして取得する
+    async fn get_comments(
+        &self,
+        article_id: ArticleId,
+        article_author: &UserName,
+        viewer: Option<&UserName>,
+    ) -> Result<Vec<CommentThread>, CommentServiceError>;
+    async fn get_comment_by_id(&self, id: CommentId) -> Result<Comment, CommentServiceError>;
+    /// 記事の著者の`moderation`に従い、承認済みまたは承認待ちのコメントを作成する
+    async fn create_comment(
+        &self,
+        article_id: ArticleId,
+        article_author: UserName,
+        parent_id: Option<CommentId>,
+        author: UserName,
+        content: String,
+        moderation: CommentModeration,
+    ) -> Result<Comment, CommentServiceError>;
+    async fn update_comment(
+        &self,
+        id: CommentId,
+        content: String,
+    ) -> Result<Comment, CommentServiceError>;
+    async fn delete_comment(&self, id: CommentId) -> Result<(), CommentServiceError>;
+    /// `article_author`の記事への承認待ちのコメントを、古い順に取得する
+    async fn get_pending_comments(
+        &self,
+        article_author: &UserName,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Vec<Comment>, CommentServiceError>;
+    /// コメントを承認または却下する
+    async fn set_comment_status(
+        &self,
+        id: CommentId,
+        status: CommentStatus,
+    ) -> Result<Comment, CommentServiceError>;
+    /// 記事の削除に合わせて、記事のコメントをすべて削除する
+    async fn delete_comments_by_article(
+        &self,
+        article_id: ArticleId,
+    ) -> Result<(), CommentServiceError>;
+    /// 記事に付いた、誰でも閲覧できるコメントの数の合計を返す
+    async fn count_comments_by_articles(
+        &self,
+        article_ids: &[ArticleId],
+    ) -> Result<u64, CommentServiceError>;
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum CommentServiceError {
+    #[error("Comment not found")]
+    CommentNotFound,
+    #[error("Parent comment not found")]
+    ParentNotFound,
+    #[error("Comment must not be empty")]
+    EmptyContent,
+    #[error("Comments are disabled on this article")]
+    CommentsDisabled,
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] mongodb::error::Error),
+}
@@ -0,0 +1,22 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::models::user_name::UserName;
+
+/// `follower`が`followee`をフォローしていることを表す
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Follow {
+    pub follower: UserName,
+    pub followee: UserName,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Follow {
+    pub fn new(follower: UserName, followee: UserName) -> Self {
+        Follow {
+            follower,
+            followee,
+            created_at: Utc::now(),
+        }
+    }
+}
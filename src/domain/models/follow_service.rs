@@ -0,0 +1,60 @@
+use async_trait::async_trait;
+
+use super::{
+    article::Article, article_service::ArticleServiceError, follow::Follow,
+    page_cursor::PageCursor, user_name::UserName, user_service::UserServiceError,
+};
+
+#[async_trait]
+pub trait FollowService {
+    /// `follower`として`followee`をフォローする　既にフォローしている場合は何もしない
+    async fn follow(&self, follower: &UserName, followee: &str) -> Result<(), FollowServiceError>;
+    /// `followee`のフォローを解除する　フォローしていない場合は何もしない
+    async fn unfollow(&self, follower: &UserName, followee: &str)
+    -> Result<(), FollowServiceError>;
+    /// `user_name`をフォローしているユーザーを、フォローした日時の新しい順に取得する
+    async fn get_followers(
+        &self,
+        user_name: &str,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Vec<Follow>, FollowServiceError>;
+    /// `user_name`がフォローしているユーザーを、フォローした日時の新しい順に取得する
+    async fn get_following(
+        &self,
+        user_name: &str,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Vec<Follow>, FollowServiceError>;
+    /// `viewer`がフォローしているユーザーの記事を新しい順に取得する
+    /// `cursor`を指定した場合は、そのカーソルより前の記事を取得する
+    async fn get_timeline(
+        &self,
+        viewer: &UserName,
+        cursor: Option<PageCursor>,
+        limit: usize,
+    ) -> Result<Vec<Article>, FollowServiceError>;
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum FollowServiceError {
+    #[error("User not found")]
+    UserNotFound,
+    #[error("Users cannot follow themselves")]
+    CannotFollowSelf,
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] mongodb::error::Error),
+    #[error(transparent)]
+    UserError(UserServiceError),
+    #[error(transparent)]
+    ArticleError(#[from] ArticleServiceError),
+}
+
+impl From<UserServiceError> for FollowServiceError {
+    fn from(e: UserServiceError) -> Self {
+        match e {
+            UserServiceError::UserNotFound => FollowServiceError::UserNotFound,
+            e => FollowServiceError::UserError(e),
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, LazyLock, Mutex},
+};
+
+use ammonia::Builder;
+use chrono::{DateTime, Utc};
+use pulldown_cmark::{Options, Parser, html};
+
+use super::article::{Article, ArticleId};
+
+/// キャッシュに保持するレンダリング結果の最大数
+const MAX_CACHED_HTML: usize = 1000;
+
+/// サニタイズの設定
+/// 既定で許可されるタグに加えて、タスクリストのチェックボックスと表の列の揃え方を許可する
+/// `<script>`や`on*`属性などのイベントハンドラは既定で取り除かれる
+static SANITIZER: LazyLock<Builder<'static>> = LazyLock::new(|| {
+    let mut builder = Builder::default();
+    builder
+        .add_tags(["input"])
+        .add_tag_attributes("input", ["checked"])
+        .set_tag_attribute_value("input", "type", "checkbox")
+        .set_tag_attribute_value("input", "disabled", "")
+        .add_tag_attributes("th", ["style"])
+        .add_tag_attributes("td", ["style"])
+        .filter_style_properties(HashSet::from(["text-align"]));
+    builder
+});
+
+/// Markdown（CommonMarkとGFMの表・タスクリスト・取り消し線）をサニタイズされたHTMLに変換する
+pub fn render_markdown(markdown: &str) -> String {
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_TASKLISTS | Options::ENABLE_STRIKETHROUGH;
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, Parser::new_ext(markdown, options));
+    SANITIZER.clean(&unsafe_html).to_string()
+}
+
+/// 記事の本文をHTMLに変換した結果のキャッシュ
+/// 記事ごとに`updated_at`とともに保持し、記事が更新されていなければ再利用する
+#[derive(Clone, Default)]
+pub struct HtmlCache {
+    entries: Arc<Mutex<HashMap<ArticleId, CachedHtml>>>,
+}
+
+struct CachedHtml {
+    /// 変換した時点の記事の`updated_at`
+    updated_at: DateTime<Utc>,
+    html: Arc<str>,
+}
+
+impl HtmlCache {
+    /// 記事の本文をHTMLに変換する　キャッシュが最新であればそれを返す
+    pub fn render(&self, article: &Article) -> Arc<str> {
+        if let Some(cached) = self.entries.lock().unwrap().get(&article.id)
+            && cached.updated_at == article.updated_at
+        {
+            return cached.html.clone();
+        }
+        // 変換中はロックを解放し、他の記事の取得を妨げないようにする
+        let html: Arc<str> = render_markdown(&article.content).into();
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= MAX_CACHED_HTML && !entries.contains_key(&article.id) {
+            // 最も古い更新日時の記事を取り除く
+            if let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, cached)| cached.updated_at)
+                .map(|(id, _)| *id)
+            {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(
+            article.id,
+            CachedHtml {
+                updated_at: article.updated_at,
+                html: html.clone(),
+            },
+        );
+        html
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markdown_is_rendered_and_sanitized() {
+        let html = render_markdown(
+            "# 見出し\n\n| a | b |\n|:-:|---|\n| 1 | 2 |\n\n- [x] 完了\n- [ ] 未完了\n\n\
+             <script>alert(1)</script>\n\n<img src=\"x.png\" onerror=\"alert(1)\">\n\n\
+             [link](javascript:alert(1))",
+        );
+        assert!(html.contains("<h1>見出し</h1>"));
+        assert!(html.contains("<th style=\"text-align:center\">a</th>"));
+        // 属性の順序は保証されないため、チェックボックスの数と属性を個別に確認する
+        assert_eq!(html.matches("type=\"checkbox\"").count(), 2);
+        assert_eq!(html.matches("disabled=\"\"").count(), 2);
+        assert_eq!(html.matches("checked=\"\"").count(), 1);
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("onerror"));
+        assert!(!html.contains("javascript:"));
+    }
+}
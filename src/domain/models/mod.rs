@@ -1,6 +1,21 @@
 pub mod article;
 pub mod article_query;
+pub mod article_revision;
 pub mod article_service;
+pub mod author_stats;
+pub mod clock;
+pub mod comment;
+pub mod comment_service;
+pub mod follow;
+pub mod follow_service;
+pub mod markdown;
+pub mod page_cursor;
+pub mod password_hasher;
+pub mod reaction;
+pub mod search;
+pub mod slug;
+pub mod tag;
+pub mod title_regex;
 pub mod user;
 pub mod user_name;
 pub mod user_service;
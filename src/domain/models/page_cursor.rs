@@ -0,0 +1,54 @@
+use bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+
+/// 作成日時とIDによるキーセットページネーションの位置
+/// 作成日時が同じ場合はIDで順序を決めるため、ページの間に項目が追加・削除されても重複や欠落が起きない
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageCursor {
+    pub created_at: DateTime<Utc>,
+    pub id: ObjectId,
+}
+
+impl PageCursor {
+    pub fn new(created_at: DateTime<Utc>, id: impl Into<ObjectId>) -> Self {
+        PageCursor {
+            created_at,
+            id: id.into(),
+        }
+    }
+
+    /// クライアントに渡す文字列に変換する
+    /// 保存されている作成日時と一致させるため、ナノ秒まで含める
+    pub fn encode(&self) -> String {
+        format!(
+            "{}-{}-{}",
+            self.created_at.timestamp(),
+            self.created_at.timestamp_subsec_nanos(),
+            self.id.to_hex()
+        )
+    }
+
+    /// `encode`で変換した文字列を読み込む　形式が正しくない場合は`None`を返す
+    pub fn decode(s: &str) -> Option<Self> {
+        let mut parts = s.splitn(3, '-');
+        let secs = parts.next()?.parse().ok()?;
+        let nanos = parts.next()?.parse().ok()?;
+        let id = ObjectId::parse_str(parts.next()?).ok()?;
+        Some(PageCursor {
+            created_at: DateTime::from_timestamp(secs, nanos)?,
+            id,
+        })
+    }
+
+    /// 作成日時とIDの組がカーソルより前にあるかどうか
+    /// 新しい順の一覧では、次のページはカーソルより前にある項目になる
+    pub fn is_before(&self, created_at: DateTime<Utc>, id: impl Into<ObjectId>) -> bool {
+        (created_at, id.into()) < (self.created_at, self.id)
+    }
+
+    /// 作成日時とIDの組がカーソルより後にあるかどうか
+    /// 古い順の一覧では、次のページはカーソルより後にある項目になる
+    pub fn is_after(&self, created_at: DateTime<Utc>, id: impl Into<ObjectId>) -> bool {
+        (created_at, id.into()) > (self.created_at, self.id)
+    }
+}
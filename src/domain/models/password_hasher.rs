@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// データベースに保存されるパスワードハッシュ
+/// 旧形式のハッシュを持つユーザーも読み込めるように、形式ごとに値を区別する
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PasswordHash {
+    /// PHC文字列形式のハッシュ（`$argon2id$v=19$...`など）
+    Phc(String),
+    /// ソルトなしのSHA-256ハッシュ
+    /// 以前のバージョンで作成されたユーザーのみが持ち、ログイン時にPHC形式へ移行される
+    LegacySha256(Vec<u8>),
+}
+
+impl PasswordHash {
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            PasswordHash::Phc(phc) => phc.as_bytes(),
+            PasswordHash::LegacySha256(digest) => digest,
+        }
+    }
+
+    /// `hasher`を使ってパスワードが一致するかを検証する
+    /// 旧形式のハッシュの場合は`hasher`に関わらずSHA-256で比較する
+    pub fn verify<H: PasswordHasher>(&self, hasher: &H, password: &str) -> bool {
+        match self {
+            PasswordHash::Phc(phc) => hasher.verify(password, phc),
+            PasswordHash::LegacySha256(digest) => {
+                Sha256::digest(password.as_bytes()).as_slice() == digest.as_slice()
+            }
+        }
+    }
+
+    /// `hasher`の現在の設定でハッシュし直すべきかどうかを返す
+    pub fn needs_rehash<H: PasswordHasher>(&self, hasher: &H) -> bool {
+        match self {
+            PasswordHash::Phc(phc) => hasher.needs_rehash(phc),
+            PasswordHash::LegacySha256(_) => true,
+        }
+    }
+}
+
+/// パスワードのハッシュ化と検証を抽象化したトレイト
+/// ハッシュはPHC文字列形式で扱う
+pub trait PasswordHasher: Clone + Send + Sync {
+    /// パスワードをランダムなソルトでハッシュ化し、PHC文字列を返す
+    ///
+    /// # Errors
+    /// ハッシュ化に失敗した場合は`Err`を返す
+    fn hash(&self, password: &str) -> Result<String, PasswordHasherError>;
+
+    /// パスワードがPHC文字列のハッシュと一致するかを検証する
+    /// PHC文字列が不正な場合は`false`を返す
+    fn verify(&self, password: &str, phc: &str) -> bool;
+
+    /// PHC文字列のアルゴリズムやパラメータが現在の設定と異なる場合に`true`を返す
+    fn needs_rehash(&self, phc: &str) -> bool;
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("Failed to hash password: {0}")]
+pub struct PasswordHasherError(pub String);
@@ -0,0 +1,41 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+/// 記事に付けられる絵文字のリアクション
+/// ユーザーは記事ごとに、それぞれの種類のリアクションを1つずつ付けられる
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReactionKind {
+    /// 👍
+    Like,
+    /// ❤️
+    Love,
+    /// 😂
+    Laugh,
+    /// 😮
+    Surprised,
+    /// 😢
+    Sad,
+    /// 🎉
+    Celebrate,
+}
+
+impl ReactionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReactionKind::Like => "like",
+            ReactionKind::Love => "love",
+            ReactionKind::Laugh => "laugh",
+            ReactionKind::Surprised => "surprised",
+            ReactionKind::Sad => "sad",
+            ReactionKind::Celebrate => "celebrate",
+        }
+    }
+}
+
+impl Display for ReactionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -0,0 +1,402 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use unicode_normalization::UnicodeNormalization;
+
+use super::article::{Article, ArticleId};
+
+/// スニペットの最大文字数
+const SNIPPET_CHARS: usize = 120;
+
+/// スニペットで最初に一致した箇所より前に含める文字数
+const SNIPPET_CONTEXT_CHARS: usize = 30;
+
+/// タイトルに含まれる検索語の重み　本文に含まれる場合の何倍にするか
+pub const TITLE_WEIGHT: f64 = 3.0;
+
+/// 出現回数による加点の飽和を調整するパラメータ（BM25の`k1`に相当する）
+pub const TF_SATURATION: f64 = 1.2;
+
+/// 検索語の列が連続して現れる場合の加点
+pub const PHRASE_BONUS: f64 = 1.0;
+
+/// 関連度の計算や検索語の抽出に使用する形に正規化する
+/// 全角・半角の違いや結合文字（か＋濁点など）の違いをNFKC正規化で、大文字・小文字の違いを小文字化で吸収する
+/// データベースで関連度を計算する場合は、正規化したタイトルと本文を保存しておく
+pub fn normalize(text: &str) -> String {
+    text.nfkc().flat_map(char::to_lowercase).collect()
+}
+
+/// `normalize`と同様に正規化し、正規化後の各文字が元のどの範囲の文字に由来するかを合わせて返す
+/// 正規化で結合する文字の並びをまとめて正規化するため、結合した文字は並び全体に由来するものとする
+fn normalize_chars(text: &str) -> Vec<((usize, usize), char)> {
+    let mut normalized = Vec::new();
+    let mut push = |range: (usize, usize), segment: &str| {
+        normalized.extend(
+            segment
+                .nfkc()
+                .flat_map(char::to_lowercase)
+                .map(|c| (range, c)),
+        );
+    };
+    let mut start = 0;
+    let mut segment = String::new();
+    for (i, c) in text.chars().enumerate() {
+        if !segment.is_empty() && !composes(&segment, c) {
+            push((start, i), &segment);
+            segment.clear();
+            start = i;
+        }
+        segment.push(c);
+    }
+    if !segment.is_empty() {
+        push((start, text.chars().count()), &segment);
+    }
+    normalized
+}
+
+/// `c`が正規化で`segment`の文字と結合するかどうか
+fn composes(segment: &str, c: char) -> bool {
+    // ASCIIの文字は前の文字と結合しない
+    if c.is_ascii() {
+        return false;
+    }
+    let separate = segment.nfkc().chain(std::iter::once(c).nfkc());
+    !segment
+        .chars()
+        .chain(std::iter::once(c))
+        .nfkc()
+        .eq(separate)
+}
+
+/// 日本語（漢字・ひらがな・カタカナ）の文字かどうか
+/// 日本語は単語の区切りが空白で表されないため、n-gramで分割する
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3005}' // 々
+        | '\u{3040}'..='\u{309F}' // ひらがな
+        | '\u{30A0}'..='\u{30FF}' // カタカナ
+        | '\u{3400}'..='\u{4DBF}' // CJK統合漢字拡張A
+        | '\u{4E00}'..='\u{9FFF}' // CJK統合漢字
+        | '\u{F900}'..='\u{FAFF}' // CJK互換漢字
+    )
+}
+
+/// 正規化済みの文字列を、日本語の連続と、それ以外の英数字の連続（単語）に分ける
+/// 記号や空白は区切りとして扱う
+fn runs(normalized: &str) -> Vec<(bool, Vec<char>)> {
+    let mut runs: Vec<(bool, Vec<char>)> = Vec::new();
+    let mut current: Option<(bool, Vec<char>)> = None;
+    for c in normalized.chars() {
+        let kind = if is_cjk(c) {
+            Some(true)
+        } else if c.is_alphanumeric() {
+            Some(false)
+        } else {
+            None
+        };
+        if let (Some((cjk, chars)), Some(kind)) = (current.as_mut(), kind)
+            && *cjk == kind
+        {
+            chars.push(c);
+            continue;
+        }
+        runs.extend(current.take());
+        current = kind.map(|kind| (kind, vec![c]));
+    }
+    runs.extend(current);
+    runs
+}
+
+/// 記事のタイトルと本文から、索引に登録する検索語を抽出する
+/// 日本語は1文字と2文字のn-gram、それ以外は単語を検索語とする
+pub fn index_terms(title: &str, content: &str) -> BTreeSet<String> {
+    let mut terms = BTreeSet::new();
+    for text in [title, content] {
+        for (cjk, chars) in runs(&normalize(text)) {
+            if cjk {
+                terms.extend(chars.iter().map(char::to_string));
+                terms.extend(chars.windows(2).map(|pair| pair.iter().collect()));
+            } else {
+                terms.insert(chars.into_iter().collect());
+            }
+        }
+    }
+    terms
+}
+
+/// 検索クエリから検索語を抽出する
+/// 日本語は2文字のn-gram（1文字だけの場合はその文字）、それ以外は単語を検索語とする
+/// すべての検索語を含む記事が検索結果になる
+pub fn query_terms(q: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for (cjk, chars) in runs(&normalize(q)) {
+        let run_terms: Vec<String> = if cjk && chars.len() > 1 {
+            chars.windows(2).map(|pair| pair.iter().collect()).collect()
+        } else {
+            vec![chars.into_iter().collect()]
+        };
+        for term in run_terms {
+            if !terms.contains(&term) {
+                terms.push(term);
+            }
+        }
+    }
+    terms
+}
+
+/// 全文検索のために記事のタイトルと本文から作る索引
+/// 正規化や検索語の抽出は記事ごとに1回だけ行い、検索のたびに再利用する
+#[derive(Debug, Clone)]
+pub struct SearchDocument {
+    terms: BTreeSet<String>,
+    title: String,
+    content: String,
+}
+
+impl SearchDocument {
+    pub fn new(article: &Article) -> Self {
+        SearchDocument {
+            terms: index_terms(&article.title, &article.content),
+            title: normalize(&article.title),
+            content: normalize(&article.content),
+        }
+    }
+
+    /// すべての検索語を含むかどうか
+    pub fn contains_all(&self, terms: &[String]) -> bool {
+        terms.iter().all(|term| self.terms.contains(term))
+    }
+
+    /// 記事の検索クエリに対する関連度を計算する
+    /// 検索語の出現回数を飽和させて合計し、タイトルでの出現を重く評価する
+    /// クエリの文字列がそのまま現れる場合は加点する
+    pub fn relevance(&self, q: &str, terms: &[String]) -> f64 {
+        let saturate = |tf: usize| {
+            let tf = tf as f64;
+            tf * (TF_SATURATION + 1.0) / (tf + TF_SATURATION)
+        };
+        let mut score: f64 = terms
+            .iter()
+            .map(|term| {
+                TITLE_WEIGHT * saturate(self.title.matches(term.as_str()).count())
+                    + saturate(self.content.matches(term.as_str()).count())
+            })
+            .sum();
+
+        let phrase = normalize(q.trim());
+        if !phrase.is_empty() {
+            if self.title.contains(&phrase) {
+                score += TITLE_WEIGHT * PHRASE_BONUS;
+            }
+            if self.content.contains(&phrase) {
+                score += PHRASE_BONUS;
+            }
+        }
+        score
+    }
+}
+
+/// 記事ごとの`SearchDocument`のキャッシュ
+/// `HtmlCache`と同様に、記事ごとに`updated_at`とともに保持し、記事が更新されていなければ再利用する
+#[derive(Debug, Clone, Default)]
+pub struct SearchDocumentCache {
+    entries: Arc<Mutex<HashMap<ArticleId, CachedSearchDocument>>>,
+}
+
+#[derive(Debug)]
+struct CachedSearchDocument {
+    /// 索引を作った時点の記事の`updated_at`と`version`
+    /// 同じ時刻に更新された場合も作り直すように、`version`も比較する
+    updated_at: DateTime<Utc>,
+    version: u32,
+    document: Arc<SearchDocument>,
+}
+
+impl SearchDocumentCache {
+    /// 記事の索引を返す　キャッシュが最新であればそれを返す
+    pub fn get(&self, article: &Article) -> Arc<SearchDocument> {
+        if let Some(cached) = self.entries.lock().unwrap().get(&article.id)
+            && cached.updated_at == article.updated_at
+            && cached.version == article.version
+        {
+            return cached.document.clone();
+        }
+        let document = Arc::new(SearchDocument::new(article));
+        self.entries.lock().unwrap().insert(
+            article.id,
+            CachedSearchDocument {
+                updated_at: article.updated_at,
+                version: article.version,
+                document: document.clone(),
+            },
+        );
+        document
+    }
+
+    /// 削除された記事の索引を取り除く
+    pub fn remove(&self, id: ArticleId) {
+        self.entries.lock().unwrap().remove(&id);
+    }
+}
+
+/// 本文から検索語の周辺を切り出し、一致した箇所を`<mark>`で囲んだスニペットを作る
+/// 本文はHTMLとしてエスケープし、改行は空白に置き換える
+/// 本文に検索語が現れない場合は、本文の先頭を返す
+pub fn snippet(content: &str, terms: &[String]) -> String {
+    let original: Vec<char> = content.chars().collect();
+    let (origins, normalized): (Vec<(usize, usize)>, Vec<char>) =
+        normalize_chars(content).into_iter().unzip();
+
+    // 一致した箇所を元の文字の範囲に変換し、重なる範囲をまとめる
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for term in terms {
+        let term: Vec<char> = term.chars().collect();
+        if term.is_empty() {
+            continue;
+        }
+        for (start, window) in normalized.windows(term.len()).enumerate() {
+            if window == term.as_slice() {
+                ranges.push((origins[start].0, origins[start + term.len() - 1].1));
+            }
+        }
+    }
+    ranges.sort_unstable();
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some((_, last_end)) if start <= *last_end => *last_end = (*last_end).max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+
+    let window_start = merged
+        .first()
+        .map_or(0, |(start, _)| start.saturating_sub(SNIPPET_CONTEXT_CHARS));
+    let window_end = (window_start + SNIPPET_CHARS).min(original.len());
+
+    let mut snippet = String::new();
+    if window_start > 0 {
+        snippet.push('…');
+    }
+    let mut position = window_start;
+    for (start, end) in merged {
+        let (start, end) = (start.max(position), end.min(window_end));
+        if start >= end {
+            continue;
+        }
+        push_escaped(&mut snippet, &original[position..start]);
+        snippet.push_str("<mark>");
+        push_escaped(&mut snippet, &original[start..end]);
+        snippet.push_str("</mark>");
+        position = end;
+    }
+    push_escaped(&mut snippet, &original[position..window_end]);
+    if window_end < original.len() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+fn push_escaped(out: &mut String, chars: &[char]) {
+    for &c in chars {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            '\r' | '\n' | '\t' => out.push(' '),
+            c => out.push(c),
+        }
+    }
+}
+
+/// 全文検索で見つかった記事と、その関連度・スニペット
+#[derive(Serialize, Debug, Clone)]
+pub struct SearchHit {
+    #[serde(flatten)]
+    pub article: Article,
+    pub score: f64,
+    /// 本文のうち検索語を含む部分　一致した箇所は`<mark>`で囲まれ、それ以外はHTMLとしてエスケープされている
+    pub snippet: String,
+}
+
+/// 全文検索の結果
+#[derive(Debug, Clone)]
+pub struct SearchResults {
+    pub hits: Vec<SearchHit>,
+    /// 検索語をすべて含み、条件を満たす記事の総数
+    pub total: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn japanese_text_is_split_into_ngrams() {
+        assert_eq!(query_terms("東京タワー"), ["東京", "京タ", "タワ", "ワー"]);
+        assert_eq!(query_terms("猫"), ["猫"]);
+        // 全角英数字や大文字は正規化され、記号は区切りとして扱われる
+        assert_eq!(query_terms("ＲＵＳＴ入門!"), ["rust", "入門"]);
+
+        let terms = index_terms("Rustの所有権", "");
+        for term in ["rust", "の", "所", "所有", "有権"] {
+            assert!(terms.contains(term), "{term}");
+        }
+        assert!(
+            query_terms("所有権")
+                .iter()
+                .all(|term| terms.contains(term))
+        );
+    }
+
+    #[test]
+    fn snippet_highlights_matches_and_escapes_html() {
+        let content = format!(
+            "{}<b>東京タワー</b>に行った。東京は楽しい。",
+            "あ".repeat(50)
+        );
+        let highlighted = snippet(&content, &query_terms("東京"));
+        assert!(highlighted.starts_with('…'));
+        assert!(highlighted.contains("&lt;b&gt;<mark>東京</mark>タワー&lt;/b&gt;"));
+        assert!(highlighted.contains("。<mark>東京</mark>は楽しい。"));
+
+        // 重なる検索語は1つの範囲にまとめる
+        assert_eq!(
+            snippet("東京タワー", &query_terms("東京タワー")),
+            "<mark>東京タワー</mark>"
+        );
+    }
+
+    #[test]
+    fn decomposed_text_is_composed_before_tokenizing() {
+        // 濁点を結合文字で表した「がくしゅう」と半角カタカナの「ガイド」
+        let decomposed = "\u{304B}\u{3099}くしゅう ｶﾞｲﾄﾞ Cafe\u{301}";
+        assert_eq!(normalize(decomposed), "がくしゅう ガイド café");
+        assert_eq!(
+            query_terms(decomposed),
+            query_terms("がくしゅう ガイド café")
+        );
+
+        let terms = index_terms(decomposed, "");
+        for term in ["が", "がく", "ガイ", "café"] {
+            assert!(terms.contains(term), "{term}");
+        }
+
+        // 一致した箇所は、結合文字を含む元の文字の範囲で囲む
+        assert_eq!(
+            snippet(decomposed, &query_terms("ガイド")),
+            "\u{304B}\u{3099}くしゅう <mark>ｶﾞｲﾄﾞ</mark> Cafe\u{301}"
+        );
+        assert_eq!(
+            snippet(decomposed, &query_terms("が")),
+            "<mark>\u{304B}\u{3099}</mark>くしゅう ｶﾞｲﾄﾞ Cafe\u{301}"
+        );
+    }
+}
@@ -0,0 +1,306 @@
+use bson::oid::ObjectId;
+use unicode_normalization::UnicodeNormalization;
+
+use super::article::ArticleId;
+
+/// スラッグの最大文字数
+pub const MAX_SLUG_CHARS: usize = 60;
+
+/// タイトルからスラッグを作れなかった場合に使用するスラッグ
+pub const FALLBACK_SLUG: &str = "article";
+
+/// 記事のIDから作る識別子の文字数
+const ID_TAG_CHARS: usize = 8;
+
+/// 記事のタイトルからURLに使用するスラッグを作る
+/// 英数字は小文字にし、ひらがな・カタカナはヘボン式のローマ字に変換する
+/// 記号や空白は区切りとして扱い、区切りや文字の種類が変わる位置を`-`でつなぐ
+/// 漢字などの変換できない文字を含む場合は、残った部分だけでは他の記事と重複しやすいため、末尾に`id`から作った識別子を付ける
+/// 使用できる文字が1つもない場合は`FALLBACK_SLUG`に識別子を付ける
+pub fn slugify(title: &str, id: ArticleId) -> String {
+    let (words, dropped) = transliterate(title);
+    if !dropped && let Some(slug) = join_words(&words, MAX_SLUG_CHARS) {
+        return slug;
+    }
+    let tag = id_tag(id);
+    let base = join_words(&words, MAX_SLUG_CHARS - 1 - tag.len())
+        .unwrap_or_else(|| FALLBACK_SLUG.to_string());
+    format!("{base}-{tag}")
+}
+
+/// 記事のIDの末尾から識別子を作る
+/// 末尾はプロセスごとのカウンタのため、同じサーバーで作成した記事の間では重複しない
+fn id_tag(id: ArticleId) -> String {
+    let hex = ObjectId::from(id).to_hex();
+    hex[hex.len() - ID_TAG_CHARS..].to_string()
+}
+
+/// タイトルの文字をスラッグに使用できる単語に変換する
+/// 漢字など、区切り以外で変換できずに取り除いた文字があった場合は`true`を合わせて返す
+fn transliterate(title: &str) -> (Vec<String>, bool) {
+    let chars: Vec<(char, Option<Script>)> = title
+        .nfkc()
+        .flat_map(char::to_lowercase)
+        .map(|c| (to_hiragana(c), Script::of(c)))
+        .collect();
+
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+    let mut current: Option<Script> = None;
+    // 直前が促音（っ）の場合は、次のローマ字の子音を重ねる
+    let mut sokuon = false;
+    let mut dropped = false;
+    let mut i = 0;
+    while i < chars.len() {
+        let (c, script) = chars[i];
+        i += 1;
+        if script != current {
+            words.extend(take_word(&mut word));
+            current = script;
+        }
+        let romaji = match script {
+            Some(Script::Latin) => c.to_string(),
+            Some(Script::Hiragana | Script::Katakana) if c == 'っ' => {
+                sokuon = true;
+                continue;
+            }
+            // 長音は表記しない
+            Some(Script::Katakana) if c == 'ー' => continue,
+            Some(Script::Hiragana | Script::Katakana) if let Some(romaji) = kana_romaji(c) => {
+                match chars
+                    .get(i)
+                    .and_then(|&(next, _)| combine_small_kana(romaji, next))
+                {
+                    Some(combined) => {
+                        i += 1;
+                        combined
+                    }
+                    None => romaji.to_string(),
+                }
+            }
+            _ => {
+                dropped |= c.is_alphanumeric();
+                words.extend(take_word(&mut word));
+                current = None;
+                sokuon = false;
+                continue;
+            }
+        };
+        if std::mem::take(&mut sokuon)
+            && let Some(first) = romaji.chars().next()
+            && !"aiueon".contains(first)
+        {
+            word.push(first);
+        }
+        word.push_str(&romaji);
+    }
+    words.extend(take_word(&mut word));
+    (words, dropped)
+}
+
+/// 単語を`-`でつなぎ、`max_chars`文字に収まるところまでを返す　単語がない場合は`None`を返す
+fn join_words(words: &[String], max_chars: usize) -> Option<String> {
+    let mut slug = String::new();
+    for word in words {
+        let separator = usize::from(!slug.is_empty());
+        if slug.len() + separator + word.len() > max_chars {
+            if slug.is_empty() {
+                slug = word[..max_chars].to_string();
+            }
+            break;
+        }
+        if !slug.is_empty() {
+            slug.push('-');
+        }
+        slug.push_str(word);
+    }
+    (!slug.is_empty()).then_some(slug)
+}
+
+/// スラッグが重複する場合に付ける番号を加える　`n`が1の場合はそのまま返す
+pub fn numbered_slug(base: &str, n: u32) -> String {
+    if n <= 1 {
+        base.to_string()
+    } else {
+        format!("{base}-{n}")
+    }
+}
+
+/// スラッグに変換できる文字の種類　種類が変わる位置で単語を区切る
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Script {
+    /// 英数字
+    Latin,
+    Hiragana,
+    Katakana,
+}
+
+impl Script {
+    fn of(c: char) -> Option<Self> {
+        match c {
+            c if c.is_ascii_alphanumeric() => Some(Script::Latin),
+            '\u{3041}'..='\u{309F}' => Some(Script::Hiragana),
+            '\u{30A1}'..='\u{30FF}' => Some(Script::Katakana),
+            _ => None,
+        }
+    }
+}
+
+fn take_word(word: &mut String) -> Option<String> {
+    (!word.is_empty()).then(|| std::mem::take(word))
+}
+
+/// カタカナをひらがなに変換する　それ以外の文字はそのまま返す
+fn to_hiragana(c: char) -> char {
+    match c {
+        '\u{30A1}'..='\u{30F6}' => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+        c => c,
+    }
+}
+
+/// 拗音（きゃ）や外来語の表記（ふぁ）など、小書きのかなとの組み合わせをローマ字にする
+fn combine_small_kana(romaji: &str, next: char) -> Option<String> {
+    let vowel = match next {
+        'ゃ' => "a",
+        'ゅ' => "u",
+        'ょ' => "o",
+        'ぁ' | 'ぃ' | 'ぅ' | 'ぇ' | 'ぉ' => kana_romaji(next)?,
+        _ => return None,
+    };
+    let stem = romaji.strip_suffix(['a', 'i', 'u', 'e', 'o'])?;
+    if stem.is_empty() {
+        return None;
+    }
+    // し・ち・じ以外の拗音は`y`を挟む（きゃ→kya）
+    let y = if matches!(next, 'ゃ' | 'ゅ' | 'ょ') && !matches!(stem, "sh" | "ch" | "j") {
+        "y"
+    } else {
+        ""
+    };
+    Some(format!("{stem}{y}{vowel}"))
+}
+
+/// ひらがな1文字のヘボン式のローマ字
+fn kana_romaji(c: char) -> Option<&'static str> {
+    Some(match c {
+        'あ' | 'ぁ' => "a",
+        'い' | 'ぃ' | 'ゐ' => "i",
+        'う' | 'ぅ' => "u",
+        'え' | 'ぇ' | 'ゑ' => "e",
+        'お' | 'ぉ' | 'を' => "o",
+        'か' | 'ゕ' => "ka",
+        'き' => "ki",
+        'く' => "ku",
+        'け' | 'ゖ' => "ke",
+        'こ' => "ko",
+        'が' => "ga",
+        'ぎ' => "gi",
+        'ぐ' => "gu",
+        'げ' => "ge",
+        'ご' => "go",
+        'さ' => "sa",
+        'し' => "shi",
+        'す' => "su",
+        'せ' => "se",
+        'そ' => "so",
+        'ざ' => "za",
+        'じ' | 'ぢ' => "ji",
+        'ず' | 'づ' => "zu",
+        'ぜ' => "ze",
+        'ぞ' => "zo",
+        'た' => "ta",
+        'ち' => "chi",
+        'つ' => "tsu",
+        'て' => "te",
+        'と' => "to",
+        'だ' => "da",
+        'で' => "de",
+        'ど' => "do",
+        'な' => "na",
+        'に' => "ni",
+        'ぬ' => "nu",
+        'ね' => "ne",
+        'の' => "no",
+        'は' => "ha",
+        'ひ' => "hi",
+        'ふ' => "fu",
+        'へ' => "he",
+        'ほ' => "ho",
+        'ば' => "ba",
+        'び' => "bi",
+        'ぶ' => "bu",
+        'べ' => "be",
+        'ぼ' => "bo",
+        'ぱ' => "pa",
+        'ぴ' => "pi",
+        'ぷ' => "pu",
+        'ぺ' => "pe",
+        'ぽ' => "po",
+        'ま' => "ma",
+        'み' => "mi",
+        'む' => "mu",
+        'め' => "me",
+        'も' => "mo",
+        'や' | 'ゃ' => "ya",
+        'ゆ' | 'ゅ' => "yu",
+        'よ' | 'ょ' => "yo",
+        'ら' => "ra",
+        'り' => "ri",
+        'る' => "ru",
+        'れ' => "re",
+        'ろ' => "ro",
+        'わ' | 'ゎ' => "wa",
+        'ん' => "n",
+        'ゔ' => "vu",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id() -> ArticleId {
+        ArticleId::parse_str("0123456789abcdef01234567").unwrap()
+    }
+
+    #[test]
+    fn titles_are_transliterated_into_slugs() {
+        let id = id();
+        assert_eq!(slugify("Hello, World!", id), "hello-world");
+        assert_eq!(slugify("すし Sushi", id), "sushi-sushi");
+        assert_eq!(slugify("ちょっとしたコツ", id), "chottoshita-kotsu");
+        assert_eq!(slugify("ファイル・キャッシュ", id), "fairu-kyasshu");
+        assert_eq!(slugify("ｶﾀｶﾅ", id), "katakana");
+        // 変換できる文字がない場合
+        assert_eq!(slugify("!!!", id), "article-01234567");
+
+        let long = slugify(&"word ".repeat(30), id);
+        assert!(long.len() <= MAX_SLUG_CHARS);
+        assert!(!long.ends_with('-'));
+        assert_eq!(slugify(&"a".repeat(100), id).len(), MAX_SLUG_CHARS);
+
+        assert_eq!(numbered_slug("rust", 1), "rust");
+        assert_eq!(numbered_slug("rust", 3), "rust-3");
+    }
+
+    #[test]
+    fn titles_with_kanji_have_suffix_from_id() {
+        let id = id();
+        assert_eq!(slugify("ＲＵＳＴ入門", id), "rust-01234567");
+        assert_eq!(slugify("Rustの基本", id), "rust-no-01234567");
+        assert_eq!(slugify("東京タワー", id), "tawa-01234567");
+        assert_eq!(slugify("東京", id), "article-01234567");
+        assert_eq!(slugify("Ελληνικά", id), "article-01234567");
+
+        // 漢字だけが異なるタイトルでも、記事ごとにスラッグが異なる
+        let (a, b) = (ArticleId::new(), ArticleId::new());
+        assert_ne!(slugify("Rustの基本", a), slugify("Rustの応用", b));
+        assert_ne!(slugify("東京", a), slugify("東京", b));
+
+        let long = slugify(&format!("{}東京", "word ".repeat(30)), id);
+        assert!(long.len() <= MAX_SLUG_CHARS);
+        assert!(long.ends_with("-01234567"));
+        assert!(!long.contains("--"));
+    }
+}
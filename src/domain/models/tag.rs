@@ -0,0 +1,31 @@
+use serde::Serialize;
+use unicode_normalization::UnicodeNormalization;
+
+/// タグを正規化する
+/// 全角・半角の違いをNFKC正規化で、大文字・小文字の違いを小文字化で吸収し、前後の空白を取り除く
+/// 空のタグの場合は`None`を返す
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let normalized: String = tag.nfkc().collect::<String>().trim().to_lowercase();
+    (!normalized.is_empty()).then_some(normalized)
+}
+
+/// タグのリストを正規化し、空のタグと重複を取り除く
+/// 最初に現れた順序を保つ
+pub fn normalize_tags(tags: impl IntoIterator<Item = impl AsRef<str>>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        if let Some(tag) = normalize_tag(tag.as_ref())
+            && !normalized.contains(&tag)
+        {
+            normalized.push(tag);
+        }
+    }
+    normalized
+}
+
+/// タグと、そのタグが付いた公開記事の数
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: u64,
+}
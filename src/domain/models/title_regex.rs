@@ -0,0 +1,173 @@
+use std::time::Duration;
+
+use regex::Regex;
+use regex_syntax::{
+    ast::{
+        self, AssertionKind, Ast, ClassSet, ClassSetItem, Flag, FlagsItemKind, GroupKind,
+        LiteralKind, SpecialLiteralKind,
+    },
+    hir::{Hir, HirKind},
+};
+
+/// タイトルの正規表現の最大文字数
+pub const MAX_TITLE_REGEX_CHARS: usize = 100;
+
+/// 正規表現を含むクエリをデータベースで評価する時間の上限
+/// 構文を制限しても残る多項式時間のバックトラックは、この時間で打ち切る
+pub const TITLE_REGEX_MAX_TIME: Duration = Duration::from_secs(2);
+
+/// 繰り返しの回数に指定できる最大値
+const MAX_REPETITION: u32 = 100;
+
+/// 検証済みのタイトルの正規表現
+/// データベースで実行しても処理が爆発しないように、長さや構文を制限する
+#[derive(Debug, Clone)]
+pub struct TitleRegex {
+    regex: Regex,
+}
+
+impl TitleRegex {
+    /// 正規表現を検証する
+    /// 後方参照や先読みなど、線形時間で評価できない構文と、1文字や文字クラス以外の繰り返しは受け付けない
+    /// メモリ上とデータベース（PCRE）とで解釈が異なる構文も受け付けない
+    ///
+    /// # Errors
+    /// 長すぎる場合や、構文が正しくない場合、制限された構文を含む場合は`Err`を返す
+    pub fn new(pattern: &str) -> Result<Self, TitleRegexError> {
+        if pattern.chars().count() > MAX_TITLE_REGEX_CHARS {
+            return Err(TitleRegexError::TooLong);
+        }
+        let ast = ast::parse::Parser::new()
+            .parse(pattern)
+            .map_err(|e| TitleRegexError::Invalid(e.to_string()))?;
+        check_portable(&ast)?;
+        let hir = regex_syntax::Parser::new()
+            .parse(pattern)
+            .map_err(|e| TitleRegexError::Invalid(e.to_string()))?;
+        check_repetitions(&hir)?;
+        let regex = Regex::new(pattern).map_err(|e| TitleRegexError::Invalid(e.to_string()))?;
+        Ok(TitleRegex { regex })
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.regex.as_str()
+    }
+
+    pub fn is_match(&self, title: &str) -> bool {
+        self.regex.is_match(title)
+    }
+}
+
+/// メモリ上の`regex`とデータベースのPCREとで同じように解釈される構文のみを含むかを確認する
+/// `\d`や`\w`、`\b`は`regex`ではUnicodeの文字を含むがPCREではASCIIのみのため、`[0-9]`などで指定する
+fn check_portable(ast: &Ast) -> Result<(), TitleRegexError> {
+    match ast {
+        Ast::Empty(_) | Ast::Dot(_) => Ok(()),
+        Ast::Literal(literal) => check_literal(literal),
+        Ast::Assertion(assertion) => match assertion.kind {
+            AssertionKind::StartLine
+            | AssertionKind::EndLine
+            | AssertionKind::StartText
+            | AssertionKind::EndText => Ok(()),
+            _ => Err(TitleRegexError::Unsupported("word boundaries")),
+        },
+        Ast::ClassPerl(_) => Err(TitleRegexError::Unsupported("\\d, \\s or \\w")),
+        Ast::ClassUnicode(_) => Err(TitleRegexError::Unsupported("Unicode classes")),
+        Ast::ClassBracketed(class) => check_class_set(&class.kind),
+        Ast::Flags(set_flags) => check_flags(&set_flags.flags),
+        Ast::Group(group) => {
+            if let GroupKind::NonCapturing(flags) = &group.kind {
+                check_flags(flags)?;
+            }
+            check_portable(&group.ast)
+        }
+        Ast::Repetition(repetition) => check_portable(&repetition.ast),
+        Ast::Alternation(alternation) => alternation.asts.iter().try_for_each(check_portable),
+        Ast::Concat(concat) => concat.asts.iter().try_for_each(check_portable),
+    }
+}
+
+/// `\v`はPCREでは垂直方向の空白のクラス、`\u`はPCREでは使用できないため受け付けない
+fn check_literal(literal: &ast::Literal) -> Result<(), TitleRegexError> {
+    match literal.kind {
+        LiteralKind::Verbatim | LiteralKind::Meta | LiteralKind::Superfluous => Ok(()),
+        LiteralKind::HexFixed(ast::HexLiteralKind::X)
+        | LiteralKind::HexBrace(ast::HexLiteralKind::X) => Ok(()),
+        LiteralKind::Special(ref special) if *special != SpecialLiteralKind::VerticalTab => Ok(()),
+        _ => Err(TitleRegexError::Unsupported("this escape sequence")),
+    }
+}
+
+/// 文字クラスの入れ子や`&&`などの集合演算は、PCREでは文字として解釈されるため受け付けない
+fn check_class_set(set: &ClassSet) -> Result<(), TitleRegexError> {
+    fn check_item(item: &ClassSetItem) -> Result<(), TitleRegexError> {
+        match item {
+            ClassSetItem::Empty(_) | ClassSetItem::Ascii(_) => Ok(()),
+            ClassSetItem::Literal(literal) => check_literal(literal),
+            ClassSetItem::Range(range) => {
+                check_literal(&range.start)?;
+                check_literal(&range.end)
+            }
+            ClassSetItem::Union(union) => union.items.iter().try_for_each(check_item),
+            ClassSetItem::Perl(_) => Err(TitleRegexError::Unsupported("\\d, \\s or \\w")),
+            ClassSetItem::Unicode(_) => Err(TitleRegexError::Unsupported("Unicode classes")),
+            ClassSetItem::Bracketed(_) => Err(TitleRegexError::Unsupported("nested classes")),
+        }
+    }
+    match set {
+        ClassSet::Item(item) => check_item(item),
+        ClassSet::BinaryOp(_) => Err(TitleRegexError::Unsupported("class set operations")),
+    }
+}
+
+/// `i`・`m`・`s`以外のフラグは、PCREにないか解釈が異なるため受け付けない
+fn check_flags(flags: &ast::Flags) -> Result<(), TitleRegexError> {
+    flags.items.iter().try_for_each(|item| match item.kind {
+        FlagsItemKind::Negation
+        | FlagsItemKind::Flag(Flag::CaseInsensitive | Flag::MultiLine | Flag::DotMatchesNewLine) => {
+            Ok(())
+        }
+        FlagsItemKind::Flag(_) => Err(TitleRegexError::Unsupported("flags other than i, m and s")),
+    })
+}
+
+/// 繰り返すのが1つの文字列か文字クラスのみか、回数が多すぎないかを確認する
+/// 選択や連結、繰り返しを繰り返すと、バックトラックを行う正規表現エンジンで指数時間かかることがある（`(a|aa)*`など）
+fn check_repetitions(hir: &Hir) -> Result<(), TitleRegexError> {
+    match hir.kind() {
+        HirKind::Repetition(repetition) => {
+            if repetition.min > MAX_REPETITION
+                || repetition.max.is_some_and(|max| max > MAX_REPETITION)
+            {
+                return Err(TitleRegexError::TooManyRepetitions);
+            }
+            let mut sub = &*repetition.sub;
+            while let HirKind::Capture(capture) = sub.kind() {
+                sub = &capture.sub;
+            }
+            match sub.kind() {
+                HirKind::Literal(_) | HirKind::Class(_) => Ok(()),
+                _ => Err(TitleRegexError::ComplexRepetition),
+            }
+        }
+        HirKind::Capture(capture) => check_repetitions(&capture.sub),
+        HirKind::Concat(hirs) | HirKind::Alternation(hirs) => {
+            hirs.iter().try_for_each(check_repetitions)
+        }
+        HirKind::Empty | HirKind::Literal(_) | HirKind::Class(_) | HirKind::Look(_) => Ok(()),
+    }
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum TitleRegexError {
+    #[error("title_regex must be at most {MAX_TITLE_REGEX_CHARS} characters")]
+    TooLong,
+    #[error("title_regex is invalid: {0}")]
+    Invalid(String),
+    #[error("title_regex may only repeat a single literal or character class")]
+    ComplexRepetition,
+    #[error("title_regex must not contain {0}")]
+    Unsupported(&'static str),
+    #[error("title_regex must not repeat more than {MAX_REPETITION} times")]
+    TooManyRepetitions,
+}
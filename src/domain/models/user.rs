@@ -5,7 +5,7 @@ use bson::oid::ObjectId;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::domain::models::user_name::UserName;
+use crate::domain::models::{password_hasher::PasswordHash, user_name::UserName};
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct User {
@@ -16,8 +16,69 @@ pub struct User {
     pub intro: String,
     pub email: String,
     pub show_email: bool,
-    pub pw_hash: Vec<u8>, // ハッシュ化されたパスワード
+    pub pw_hash: PasswordHash, // ハッシュ化されたパスワード
     pub created_at: DateTime<Utc>,
+    /// 最後に更新された日時　更新日時が導入される前に作成され、その後更新されていないユーザーは`None`になる
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub role: UserRole,
+    /// 自分の記事へのコメントの扱い
+    #[serde(default)]
+    pub comment_moderation: CommentModeration,
+    /// 楽観的排他制御に使用するバージョン　ユーザー情報が変更されるたびに1増える
+    /// バージョンが導入される前のユーザーは、更新されるまで`0`になる
+    #[serde(default)]
+    pub version: u32,
+}
+
+impl User {
+    pub fn is_admin(&self) -> bool {
+        self.role == UserRole::Admin
+    }
+
+    /// 最後に更新された日時　更新日時を持たない場合は作成日時を返す
+    pub fn last_modified(&self) -> DateTime<Utc> {
+        self.updated_at.unwrap_or(self.created_at)
+    }
+}
+
+/// ユーザーの権限
+/// 既存のユーザーはフィールドを持たないため、デフォルトは一般ユーザーとする
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UserRole {
+    #[default]
+    User,
+    /// 他のユーザーの代わりに記事を投稿するなどの管理操作ができる
+    Admin,
+}
+
+/// 記事へのコメントを承認する方法
+/// 既存のユーザーはフィールドを持たないため、デフォルトは自動承認とする
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommentModeration {
+    /// コメントをすぐに公開する
+    #[default]
+    AutoApprove,
+    /// 記事の著者が承認するまでコメントを保留する
+    Hold,
+    /// コメントを受け付けない
+    Disabled,
+}
+
+/// ユーザーを削除するときの、ユーザーの記事の扱い
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArticleDeletionPolicy {
+    /// 記事を削除されたユーザー（`UserName::deleted`）の記事として残す
+    #[default]
+    Reassign,
+    /// 記事とそのコメントをすべて削除する
+    Cascade,
+    /// 記事が1つでもある場合は、ユーザーを削除しない
+    Reject,
 }
 
 impl AuthUser for User {
@@ -27,7 +88,7 @@ impl AuthUser for User {
         self.id
     }
     fn session_auth_hash(&self) -> &[u8] {
-        &self.pw_hash
+        self.pw_hash.as_bytes()
     }
 }
 
@@ -44,6 +105,12 @@ impl UserId {
     }
 }
 
+impl From<UserId> for ObjectId {
+    fn from(id: UserId) -> Self {
+        id.inner
+    }
+}
+
 impl Serialize for UserId {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
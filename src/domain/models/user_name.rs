@@ -1,6 +1,13 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+/// システムが使用する名前の先頭の文字　この文字で始まる名前のユーザーは登録できない
+pub const RESERVED_NAME_PREFIX: char = '~';
+
+/// 削除されたユーザーの記事を引き継ぐ、ユーザーとしては存在しない名前
+/// 誰も記事を引き継がないように、登録できない文字（`RESERVED_NAME_PREFIX`）で始める
+pub const DELETED_USER_NAME: &str = "~deleted-user";
+
 /// UserNameは、ユーザー名を表す構造体です。
 /// ユーザー名は一意であり、文字列として表現されます
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, Hash)]
@@ -18,6 +25,19 @@ impl UserName {
     pub fn new(name: String) -> Self {
         UserName { inner: name }
     }
+
+    /// システムが使用するために予約された名前かどうか
+    /// 予約された名前はユーザー名として登録したり、変更したりできない
+    pub fn is_reserved(name: &str) -> bool {
+        name.starts_with(RESERVED_NAME_PREFIX)
+    }
+
+    /// 削除されたユーザーの記事を引き継ぐ名前（`DELETED_USER_NAME`）
+    pub fn deleted() -> Self {
+        UserName {
+            inner: DELETED_USER_NAME.to_string(),
+        }
+    }
 }
 
 impl fmt::Display for UserName {
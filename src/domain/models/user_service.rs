@@ -1,10 +1,25 @@
 use async_trait::async_trait;
 
-use super::{user::User, user_name::UserName};
+use super::{
+    article_service::ArticleServiceError,
+    comment_service::CommentServiceError,
+    follow_service::FollowServiceError,
+    page_cursor::PageCursor,
+    password_hasher::PasswordHasherError,
+    user::{ArticleDeletionPolicy, CommentModeration, User, UserId, UserRole},
+    user_name::UserName,
+};
 
 #[async_trait]
 pub trait UserService {
-    async fn get_users(&self, skip: usize, limit: usize) -> Result<Vec<User>, UserServiceError>;
+    async fn get_users(
+        &self,
+        skip: usize,
+        limit: usize,
+        cursor: Option<PageCursor>,
+    ) -> Result<Vec<User>, UserServiceError>;
+    async fn count_users(&self) -> Result<u64, UserServiceError>;
+    async fn get_user_by_id(&self, id: UserId) -> Result<User, UserServiceError>;
     async fn get_user_by_name(&self, name: &str) -> Result<User, UserServiceError>;
     async fn create_user(
         &self,
@@ -15,6 +30,7 @@ pub trait UserService {
         show_email: bool,
         password: String,
     ) -> Result<User, UserServiceError>;
+    #[allow(clippy::too_many_arguments)]
     async fn update_user(
         &self,
         name: String,
@@ -23,9 +39,29 @@ pub trait UserService {
         email: Option<String>,
         show_email: Option<bool>,
         password: Option<String>,
+        comment_moderation: Option<CommentModeration>,
+        expected_version: Option<u32>,
     ) -> Result<User, UserServiceError>;
-    async fn delete_user(&self, name: &str) -> Result<(), UserServiceError>;
+    async fn set_role(&self, name: &str, role: UserRole) -> Result<User, UserServiceError>;
+    /// ユーザーを削除する
+    /// 同じ名前で登録したユーザーが引き継がないように、記事を`articles`に従って処理し、
+    /// コメントの著者を変更してフォローとリアクションを削除してから、ユーザーを削除する
+    /// 処理は不可分ではなく、途中で失敗した場合は`DeletionIncomplete`を返す　もう一度呼び出すと続きから処理する
+    /// 処理している間に記事が作成された場合は、ユーザーを削除せずに`HasArticles`を返す
+    async fn delete_user(
+        &self,
+        name: &str,
+        expected_version: Option<u32>,
+        articles: ArticleDeletionPolicy,
+    ) -> Result<(), UserServiceError>;
     async fn validate_user_name(&self, name: &str) -> Result<UserName, UserServiceError>;
+    /// ユーザー名とパスワードを検証する
+    /// 認証に成功した場合は`Ok(Some(User))`、ユーザーが存在しないかパスワードが一致しない場合は`Ok(None)`を返す
+    async fn authenticate(
+        &self,
+        name: &str,
+        password: &str,
+    ) -> Result<Option<User>, UserServiceError>;
 }
 
 #[derive(Debug, Clone, thiserror::Error)]
@@ -34,6 +70,36 @@ pub enum UserServiceError {
     UserNotFound,
     #[error("User already exists")]
     UserAlreadyExists,
+    #[error("User name is reserved")]
+    ReservedUserName,
+    #[error("User has been modified")]
+    VersionMismatch,
+    #[error("User still has articles")]
+    HasArticles,
+    /// ユーザーの記事などを処理している途中で失敗し、ユーザーは削除されていない
+    /// 処理済みの内容は元に戻らないが、削除をもう一度行うと続きから処理される
+    #[error("User deletion was interrupted and can be retried: {0}")]
+    DeletionIncomplete(Box<UserServiceError>),
     #[error("Database error: {0}")]
     DatabaseError(mongodb::error::Error),
+    #[error(transparent)]
+    PasswordHashError(#[from] PasswordHasherError),
+    #[error(transparent)]
+    ArticleError(#[from] ArticleServiceError),
+    #[error(transparent)]
+    CommentError(#[from] CommentServiceError),
+}
+
+/// `FollowServiceError`は`UserServiceError`を含むため、変換して保持する
+impl From<FollowServiceError> for UserServiceError {
+    fn from(e: FollowServiceError) -> Self {
+        match e {
+            FollowServiceError::UserError(e) => e,
+            FollowServiceError::ArticleError(e) => UserServiceError::ArticleError(e),
+            FollowServiceError::DatabaseError(e) => UserServiceError::DatabaseError(e),
+            FollowServiceError::UserNotFound | FollowServiceError::CannotFollowSelf => {
+                UserServiceError::UserNotFound
+            }
+        }
+    }
 }
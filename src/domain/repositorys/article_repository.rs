@@ -1,16 +1,27 @@
+use std::collections::HashMap;
+
 use crate::domain::models::{
-    article::{Article, ArticleId},
-    article_query::ArticleQuery,
+    article::{Article, ArticleId, Visibility},
+    article_query::{ArticleQuery, ArticleSort},
+    article_revision::ArticleRevision,
     article_service::ArticleServiceError,
+    author_stats::AuthorArticleStats,
+    page_cursor::PageCursor,
+    reaction::ReactionKind,
+    tag::TagCount,
     user_name::UserName,
 };
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 
 /// Articleのデータベースを管理する操作を抽象化したトレイト
 #[async_trait]
 pub trait ArticleRepository {
     /// 取得した記事のリストを返す
-    /// `skip`: 取得開始位置, `limit`: 最大取得数
+    /// `skip`: 取得開始位置, `limit`: 最大取得数, `sort`: 並び順, `viewer`: 記事を閲覧するユーザー（未ログインの場合は`None`）
+    /// `cursor`: 指定した場合は並び順でこのカーソルより後の記事のみを返す（`skip`はその後に適用する）
+    /// 並び順がカーソルに対応していない場合（`ArticleSort::supports_cursor`）、`cursor`は無視する
+    /// `viewer`に対して一覧に表示される記事（`Article::is_listed_for`）のみを返す
     ///
     /// # Errors
     /// 記事の情報にアクセスできなかった場合は`Err`を返す
@@ -18,6 +29,9 @@ pub trait ArticleRepository {
         &self,
         skip: usize,
         limit: usize,
+        sort: ArticleSort,
+        cursor: Option<PageCursor>,
+        viewer: Option<&UserName>,
     ) -> Result<Vec<Article>, ArticleServiceError>;
 
     /// IDを元に記事を取得する
@@ -28,44 +42,97 @@ pub trait ArticleRepository {
     /// 記事が存在しない場合や、データベースへのアクセスに失敗した場合は`Err`を返す
     async fn get_article_by_id(&self, id: ArticleId) -> Result<Article, ArticleServiceError>;
 
+    /// 著者とスラッグを元に記事を取得する
+    /// `author`: 記事の著者のユーザー名, `slug`: 現在または過去のスラッグ
+    ///
+    /// # Errors
+    /// 記事が存在しない場合や、データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn get_article_by_slug(
+        &self,
+        author: &str,
+        slug: &str,
+    ) -> Result<Article, ArticleServiceError>;
+
     /// 新しい記事を追加する
-    /// `title`: 記事のタイトル, `author`: 記事の著者, `content`: 記事の内容
-    /// 追加された記事を返す
+    /// `id`: 記事のID（`ArticleId::new`で作成したもの）, `title`: 記事のタイトル, `slug`: 著者ごとに一意なスラッグ, `author`: 記事の著者, `content`: 記事の内容
+    /// `visibility`: 記事の公開範囲, `publish_at`: 予約投稿の日時, `tags`: 正規化されたタグ
+    /// 最初の版も記録し、追加された記事を返す
     ///
     /// # Errors
-    /// 記事の追加に失敗した場合は`Err`を返す
+    /// 同じ著者の記事が既にスラッグを使用している場合や、記事の追加に失敗した場合は`Err`を返す
+    #[allow(clippy::too_many_arguments)]
     async fn add_article(
         &self,
+        id: ArticleId,
         title: String,
+        slug: String,
         author: UserName,
         content: String,
+        visibility: Visibility,
+        publish_at: Option<DateTime<Utc>>,
+        tags: Vec<String>,
     ) -> Result<Article, ArticleServiceError>;
 
     /// 記事を更新する
-    /// `id`: 記事のID, `title`: 新しいタイトル, `content`: 新しい内容
+    /// `id`: 記事のID, `title`: 新しいタイトル, `slug`: 新しいスラッグ, `content`: 新しい内容, `visibility`: 新しい公開範囲
+    /// `publish_at`: 新しい予約投稿の日時（`Some(None)`の場合は予約を取り消す）, `tags`: 正規化された新しいタグ
+    /// `expected_version`: 更新の前提となるバージョン（`None`の場合は確認しない）
     /// 更新が成功した場合は`Ok(Article)`
-    /// `title`と`content`のいずれかが`None`の場合は更新しない
+    /// `title`、`slug`、`content`、`visibility`、`publish_at`、`tags`のうち`None`のものは更新しない
+    /// `slug`を更新した場合は、スラッグの履歴にも加える
+    /// `title`か`content`を更新した場合は版番号を進め、新しい版を記録する
+    /// 版が導入される前の記事（版番号が`0`）は、更新前の内容を最初の版として記録してから更新する
+    /// バージョンの確認と更新は不可分に行い、更新のたびにバージョンを1増やす
     ///
     /// # Errors
-    /// 記事が存在しない場合や、データベースへのアクセスに失敗した場合は`Err`を返す
+    /// 記事が存在しない場合や、バージョンが`expected_version`と異なる場合、
+    /// 同じ著者の他の記事が既にスラッグを使用している場合、データベースへのアクセスに失敗した場合は`Err`を返す
+    #[allow(clippy::too_many_arguments)]
     async fn update_article(
         &self,
         id: ArticleId,
         title: Option<String>,
+        slug: Option<String>,
         content: Option<String>,
+        visibility: Option<Visibility>,
+        publish_at: Option<Option<DateTime<Utc>>>,
+        tags: Option<Vec<String>>,
+        expected_version: Option<u32>,
     ) -> Result<Article, ArticleServiceError>;
 
+    /// スラッグを持たない記事をすべて取得する
+    /// スラッグが導入される前に作成された記事にスラッグを割り当てるために使用する
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn get_articles_without_slug(&self) -> Result<Vec<Article>, ArticleServiceError>;
+
+    /// スラッグを持たない記事にスラッグを割り当てる
+    /// 記事の内容ではないため、`updated_at`や`version`は変更しない
+    /// 既にスラッグを持つ場合は何もしない
+    ///
+    /// # Errors
+    /// 同じ著者の記事が既にスラッグを使用している場合や、データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn assign_slug(&self, id: ArticleId, slug: String) -> Result<(), ArticleServiceError>;
+
     /// 記事を削除する
-    /// `id`: 記事のID
+    /// `id`: 記事のID, `expected_version`: 削除の前提となるバージョン（`None`の場合は確認しない）
+    /// 記事の版は、削除された記事の履歴として残す
     /// 削除が成功した場合は`Ok(())`
     ///
     /// # Errors
-    /// 記事が存在しない場合や、データベースへのアクセスに失敗した場合は`Err`を返す
-    async fn delete_article(&self, id: ArticleId) -> Result<(), ArticleServiceError>;
+    /// 記事が存在しない場合や、バージョンが`expected_version`と異なる場合、
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn delete_article(
+        &self,
+        id: ArticleId,
+        expected_version: Option<u32>,
+    ) -> Result<(), ArticleServiceError>;
 
     /// クエリを元に記事を取得する
-    /// `skip`: 取得開始位置, `limit`: 最大取得数, `query`: 記事のクエリ
-    /// クエリにはタイトルや著者名などが含まれる
+    /// `skip`: 取得開始位置, `limit`: 最大取得数, `query`: 記事のクエリ, `viewer`: 記事を閲覧するユーザー
+    /// クエリにはタイトルや著者名、タグなどの条件と、並び順が含まれる
+    /// `get_articles`と同様に、`viewer`に対して一覧に表示される記事のみを返す
     ///
     /// # Errors
     /// データベースへのアクセスに失敗した場合は`Err`を返す
@@ -74,5 +141,248 @@ pub trait ArticleRepository {
         skip: usize,
         limit: usize,
         query: ArticleQuery,
+        viewer: Option<&UserName>,
+    ) -> Result<Vec<Article>, ArticleServiceError>;
+
+    /// 全文検索に一致する記事を関連度の高い順に取得する
+    /// `q`: 検索クエリ, `terms`: 検索語（`search::query_terms`で抽出したもの）, `query`: タイトルや著者名、タグなどの条件
+    /// `skip`: スキップする記事数, `limit`: 最大取得数, `viewer`: 記事を閲覧するユーザー
+    /// タイトルと本文から抽出した検索語（`search::index_terms`）にすべての`terms`を含み、`query`の条件を満たす記事を、
+    /// `viewer`に対して一覧に表示されるものに限って、関連度（`SearchDocument::relevance`）とともに返す
+    /// 一致するすべての記事を評価し、関連度が同じ記事は新しい順に並べる　`query`の並び順は無視する
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn search_articles_by_terms(
+        &self,
+        q: &str,
+        terms: &[String],
+        query: ArticleQuery,
+        skip: usize,
+        limit: usize,
+        viewer: Option<&UserName>,
+    ) -> Result<Vec<(f64, Article)>, ArticleServiceError>;
+
+    /// 全文検索に一致する記事の数を返す
+    /// `search_articles_by_terms`と同じ条件で数える
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn count_articles_by_terms(
+        &self,
+        terms: &[String],
+        query: ArticleQuery,
+        viewer: Option<&UserName>,
+    ) -> Result<u64, ArticleServiceError>;
+
+    /// 一覧に表示される記事の数を返す
+    /// `get_articles`と同様に、`viewer`に対して一覧に表示される記事のみを数える
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn count_articles(&self, viewer: Option<&UserName>) -> Result<u64, ArticleServiceError>;
+
+    /// クエリに一致する記事の数を返す
+    /// `get_articles_with_query`と同じ条件で数え、並び順は無視する
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn count_articles_with_query(
+        &self,
+        query: ArticleQuery,
+        viewer: Option<&UserName>,
+    ) -> Result<u64, ArticleServiceError>;
+
+    /// 指定した著者の記事のリストを返す
+    /// `author`: 記事の著者のユーザー名　その他の引数は`get_articles`と同じ
+    /// `get_articles`と同様に、`viewer`に対して一覧に表示される記事のみを返す
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn get_articles_by_author(
+        &self,
+        author: &str,
+        skip: usize,
+        limit: usize,
+        sort: ArticleSort,
+        cursor: Option<PageCursor>,
+        viewer: Option<&UserName>,
+    ) -> Result<Vec<Article>, ArticleServiceError>;
+
+    /// 指定した著者の記事の数を返す
+    /// `get_articles_by_author`と同じ条件で数える
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn count_articles_by_author(
+        &self,
+        author: &str,
+        viewer: Option<&UserName>,
+    ) -> Result<u64, ArticleServiceError>;
+
+    /// 指定した著者のすべての記事を、下書きや非公開の記事も含めて取得する
+    /// `author`: 記事の著者
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn get_all_articles_by_author(
+        &self,
+        author: &UserName,
     ) -> Result<Vec<Article>, ArticleServiceError>;
+
+    /// 指定した著者のすべての記事の数を、下書きや非公開の記事も含めて返す
+    /// `author`: 記事の著者
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn count_all_articles_by_author(
+        &self,
+        author: &UserName,
+    ) -> Result<u64, ArticleServiceError>;
+
+    /// 記事の著者を変更する
+    /// `id`: 記事のID, `author`: 新しい著者, `slug`: 新しい著者のもとでのスラッグ
+    /// 元の著者のもとでのスラッグは使用できなくなるため、スラッグの履歴は`slug`のみにする
+    /// 記事の内容は変わらないため`updated_at`は変更せず、バージョンを1増やす
+    /// 更新後の記事を返す
+    ///
+    /// # Errors
+    /// 記事が存在しない場合や、新しい著者の記事が既にスラッグを使用している場合、
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn reassign_article(
+        &self,
+        id: ArticleId,
+        author: UserName,
+        slug: String,
+    ) -> Result<Article, ArticleServiceError>;
+
+    /// 指定した著者の記事を集計する
+    /// `get_articles_by_author`と同じ条件で、記事の数と最初・最後の作成日時、リアクションの数の合計を求める
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn get_author_stats(
+        &self,
+        author: &str,
+        viewer: Option<&UserName>,
+    ) -> Result<AuthorArticleStats, ArticleServiceError>;
+
+    /// 指定したユーザーの下書きを取得する
+    /// `author`: 記事の著者, `skip`: 取得開始位置, `limit`: 最大取得数
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn get_drafts(
+        &self,
+        author: &UserName,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Vec<Article>, ArticleServiceError>;
+
+    /// 予約投稿の日時が`now`以前の下書きをすべて公開する
+    /// 公開された記事の`publish_at`は取り除かれ、`updated_at`は`now`になり、バージョンが1増える
+    /// 公開された記事を予約投稿の日時が早い順に返す
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn publish_due_articles(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Article>, ArticleServiceError>;
+
+    /// 記事の版の一覧を版番号の昇順で取得する
+    /// `id`: 記事のID
+    /// 記事が存在しない場合や版が記録されていない場合は空のリストを返す
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn get_revisions(
+        &self,
+        id: ArticleId,
+    ) -> Result<Vec<ArticleRevision>, ArticleServiceError>;
+
+    /// 記事の指定した版を取得する
+    /// `id`: 記事のID, `rev`: 版番号
+    ///
+    /// # Errors
+    /// 版が存在しない場合や、データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn get_revision(
+        &self,
+        id: ArticleId,
+        rev: u32,
+    ) -> Result<ArticleRevision, ArticleServiceError>;
+
+    /// 公開記事に付いているタグと、そのタグが付いた公開記事の数を取得する
+    /// 記事の数が多い順に並べ、同数の場合はタグの昇順に並べる
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn get_tag_counts(&self) -> Result<Vec<TagCount>, ArticleServiceError>;
+
+    /// 指定したユーザーたちの記事を新しい順に取得する
+    /// `authors`: 著者のリスト, `cursor`: 指定した場合はこのカーソルより前の記事のみを返す, `limit`: 最大取得数, `viewer`: 記事を閲覧するユーザー
+    /// `viewer`に対して一覧に表示される記事のみを返し、作成日時が同じ記事はIDの降順に並べる
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn get_articles_by_authors(
+        &self,
+        authors: &[UserName],
+        cursor: Option<PageCursor>,
+        limit: usize,
+        viewer: Option<&UserName>,
+    ) -> Result<Vec<Article>, ArticleServiceError>;
+
+    /// 記事にリアクションを付ける
+    /// `id`: 記事のID, `user`: リアクションを付けるユーザー, `kind`: リアクションの種類
+    /// 新しく付けた場合のみ、リアクションの数を不可分に1増やして`reacted_at`を更新する
+    /// 既に同じ種類のリアクションを付けている場合は何もしない
+    /// 更新後の記事を返す
+    ///
+    /// # Errors
+    /// 記事が存在しない場合や、データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn add_reaction(
+        &self,
+        id: ArticleId,
+        user: UserName,
+        kind: ReactionKind,
+    ) -> Result<Article, ArticleServiceError>;
+
+    /// 記事のリアクションを取り消す
+    /// `id`: 記事のID, `user`: リアクションを取り消すユーザー, `kind`: リアクションの種類
+    /// 取り消した場合のみ、リアクションの数を不可分に1減らして`reacted_at`を更新する
+    /// 数が0になった種類は`Article::reactions`から取り除く
+    /// 更新後の記事を返す
+    ///
+    /// # Errors
+    /// 記事が存在しない場合や、データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn remove_reaction(
+        &self,
+        id: ArticleId,
+        user: &UserName,
+        kind: ReactionKind,
+    ) -> Result<Article, ArticleServiceError>;
+
+    /// `user`が各記事に付けたリアクションを取得する
+    /// `ids`: 記事のIDのリスト, `user`: ユーザー
+    /// リアクションを付けていない記事はマップに含めない
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn get_user_reactions(
+        &self,
+        ids: &[ArticleId],
+        user: &UserName,
+    ) -> Result<HashMap<ArticleId, Vec<ReactionKind>>, ArticleServiceError>;
+
+    /// `user`がすべての記事に付けたリアクションを取得する
+    /// `user`: ユーザー
+    /// ユーザーを削除するときに、リアクションを1つずつ取り消すために使用する
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn get_reactions_by_user(
+        &self,
+        user: &UserName,
+    ) -> Result<Vec<(ArticleId, ReactionKind)>, ArticleServiceError>;
 }
@@ -0,0 +1,138 @@
+use crate::domain::models::{
+    article::ArticleId,
+    comment::{Comment, CommentId, CommentStatus},
+    comment_service::CommentServiceError,
+    user_name::UserName,
+};
+use async_trait::async_trait;
+
+/// Commentのデータベースを管理する操作を抽象化したトレイト
+#[async_trait]
+pub trait CommentRepository {
+    /// 記事のコメントを、削除されたものも含めて作成日時の昇順で取得する
+    /// `article_id`: 記事のID
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn get_comments_by_article(
+        &self,
+        article_id: ArticleId,
+    ) -> Result<Vec<Comment>, CommentServiceError>;
+
+    /// IDを元にコメントを取得する
+    /// `id`: コメントのID
+    ///
+    /// # Errors
+    /// コメントが存在しない場合や、データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn get_comment_by_id(&self, id: CommentId) -> Result<Comment, CommentServiceError>;
+
+    /// 新しいコメントを追加する
+    /// `article_id`: 記事のID, `article_author`: 記事の著者, `parent_id`: 返信先のコメントのID, `author`: コメントの著者, `content`: コメントの内容, `status`: 承認状態
+    /// 追加されたコメントを返す
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn add_comment(
+        &self,
+        article_id: ArticleId,
+        article_author: UserName,
+        parent_id: Option<CommentId>,
+        author: UserName,
+        content: String,
+        status: CommentStatus,
+    ) -> Result<Comment, CommentServiceError>;
+
+    /// コメントの内容を更新する
+    /// `id`: コメントのID, `content`: 新しい内容
+    /// 更新されたコメントを返す
+    ///
+    /// # Errors
+    /// コメントが存在しない場合や、データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn update_comment(
+        &self,
+        id: CommentId,
+        content: String,
+    ) -> Result<Comment, CommentServiceError>;
+
+    /// コメントを論理削除する
+    /// `id`: コメントのID
+    /// コメント自体は残して返信のスレッドを保ち、内容のみを消去する
+    ///
+    /// # Errors
+    /// コメントが存在しない場合や、データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn delete_comment(&self, id: CommentId) -> Result<(), CommentServiceError>;
+
+    /// 記事の著者ごとに、承認待ちで削除されていないコメントを作成日時の昇順で取得する
+    /// `article_author`: 記事の著者, `skip`: スキップする件数, `limit`: 取得する件数
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn get_pending_comments(
+        &self,
+        article_author: &UserName,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Vec<Comment>, CommentServiceError>;
+
+    /// コメントの承認状態を変更する
+    /// `id`: コメントのID, `status`: 新しい承認状態
+    /// 更新されたコメントを返す
+    ///
+    /// # Errors
+    /// コメントが存在しない場合や、データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn update_comment_status(
+        &self,
+        id: CommentId,
+        status: CommentStatus,
+    ) -> Result<Comment, CommentServiceError>;
+
+    /// 記事のコメントをすべて物理削除する
+    /// `article_id`: 記事のID
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn delete_comments_by_article(
+        &self,
+        article_id: ArticleId,
+    ) -> Result<(), CommentServiceError>;
+
+    /// 記事の著者の変更に合わせて、コメントに保持している記事の著者を変更する
+    /// `from`: 元の著者, `to`: 新しい著者
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn reassign_article_author(
+        &self,
+        from: &UserName,
+        to: &UserName,
+    ) -> Result<(), CommentServiceError>;
+
+    /// 著者の変更に合わせて、コメントの著者を変更する
+    /// `from`: 元の著者, `to`: 新しい著者
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn reassign_comment_author(
+        &self,
+        from: &UserName,
+        to: &UserName,
+    ) -> Result<(), CommentServiceError>;
+
+    /// 著者のコメントをすべて論理削除する
+    /// `author`: コメントの著者
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn delete_comments_by_author(&self, author: &UserName)
+    -> Result<(), CommentServiceError>;
+
+    /// 記事に付いた、承認済みで削除されていないコメントの数の合計を返す
+    /// `article_ids`: 記事のIDのリスト
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn count_comments_by_articles(
+        &self,
+        article_ids: &[ArticleId],
+    ) -> Result<u64, CommentServiceError>;
+}
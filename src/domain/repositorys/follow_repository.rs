@@ -0,0 +1,75 @@
+use crate::domain::models::{
+    follow::Follow, follow_service::FollowServiceError, user_name::UserName,
+};
+use async_trait::async_trait;
+
+/// フォローの関係を管理する操作を抽象化したトレイト
+#[async_trait]
+pub trait FollowRepository {
+    /// フォローを追加する
+    /// `follower`: フォローするユーザー, `followee`: フォローされるユーザー
+    /// 既にフォローしている場合は何もしない
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn add_follow(
+        &self,
+        follower: &UserName,
+        followee: &UserName,
+    ) -> Result<(), FollowServiceError>;
+
+    /// フォローを削除する
+    /// `follower`: フォローしているユーザー, `followee`: フォローされているユーザー
+    /// フォローしていない場合は何もしない
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn remove_follow(
+        &self,
+        follower: &UserName,
+        followee: &UserName,
+    ) -> Result<(), FollowServiceError>;
+
+    /// ユーザーのフォローとフォロワーを、両方向ともすべて削除する
+    /// `user`: ユーザー
+    /// ユーザーを削除するときに、同じ名前で登録したユーザーがフォローの関係を引き継がないようにする
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn remove_follows_of(&self, user: &UserName) -> Result<(), FollowServiceError>;
+
+    /// ユーザーをフォローしているユーザーを、フォローした日時の新しい順に取得する
+    /// `followee`: フォローされているユーザー, `skip`: スキップする件数, `limit`: 取得する件数
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn get_followers(
+        &self,
+        followee: &UserName,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Vec<Follow>, FollowServiceError>;
+
+    /// ユーザーがフォローしているユーザーを、フォローした日時の新しい順に取得する
+    /// `follower`: フォローしているユーザー, `skip`: スキップする件数, `limit`: 取得する件数
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn get_following(
+        &self,
+        follower: &UserName,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Vec<Follow>, FollowServiceError>;
+
+    /// ユーザーがフォローしているすべてのユーザーの名前を取得する
+    /// `follower`: フォローしているユーザー
+    /// タイムラインの記事を絞り込むために使用する
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn get_followee_names(
+        &self,
+        follower: &UserName,
+    ) -> Result<Vec<UserName>, FollowServiceError>;
+}
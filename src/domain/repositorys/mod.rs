@@ -1,2 +1,4 @@
 pub mod article_repository;
+pub mod comment_repository;
+pub mod follow_repository;
 pub mod user_repository;
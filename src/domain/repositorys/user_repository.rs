@@ -1,5 +1,7 @@
 use crate::domain::models::{
-    user::{User, UserId},
+    page_cursor::PageCursor,
+    password_hasher::PasswordHash,
+    user::{CommentModeration, User, UserId, UserRole},
     user_name::UserName,
     user_service::UserServiceError,
 };
@@ -8,11 +10,22 @@ use async_trait::async_trait;
 /// Userのデータベースを管理する操作を抽象化したトレイト
 #[async_trait]
 pub trait UserRepository {
-    /// 取得したユーザーのリストを返す
+    /// 取得したユーザーのリストを、作成日時とIDの昇順に返す
     /// `skip`: 取得開始位置, `limit`: 最大取得数
+    /// `cursor`: 指定した場合はこのカーソルより後のユーザーのみを返す（`skip`はその後に適用する）
     /// # Errors
     /// ユーザーの情報にアクセスできなかった場合は`Err`を返す
-    async fn get_users(&self, skip: usize, limit: usize) -> Result<Vec<User>, UserServiceError>;
+    async fn get_users(
+        &self,
+        skip: usize,
+        limit: usize,
+        cursor: Option<PageCursor>,
+    ) -> Result<Vec<User>, UserServiceError>;
+
+    /// ユーザーの数を返す
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn count_users(&self) -> Result<u64, UserServiceError>;
 
     /// IDを元にユーザーを取得する
     /// `id`: ユーザーのObjectId
@@ -32,7 +45,7 @@ pub trait UserRepository {
 
     // 将来的にはここの入力を構造体にまとめるかも
     /// 新しいユーザーを追加する
-    /// `name`: 追加するユーザー名, `display_name`: 表示名, `intro`: 自己紹介, `email`: メールアドレス, `show_email`: メールアドレスを公開するかどうか, `password`: パスワード
+    /// `name`: 追加するユーザー名, `display_name`: 表示名, `intro`: 自己紹介, `email`: メールアドレス, `show_email`: メールアドレスを公開するかどうか, `pw_hash`: ハッシュ化されたパスワード
     /// このメソッドは、ユーザー名の重複チェックを行う必要があります。
     /// # Errors
     /// ユーザーが既に存在する場合や、データベースへのアクセスに失敗した場合は`Err`を返す
@@ -43,15 +56,18 @@ pub trait UserRepository {
         intro: String,
         email: String,
         show_email: bool,
-        pw_hash: Vec<u8>,
+        pw_hash: PasswordHash,
     ) -> Result<User, UserServiceError>;
 
     /// ユーザー情報を部分的に更新する
-    /// `name`: 更新するユーザー名, `display_name`: 新しい表示名, `intro`: 新しい自己紹介, `email`: 新しいメールアドレス, `show_email`: メールアドレスを公開するかどうか, `password`: 新しいパスワード
+    /// `name`: 更新するユーザー名, `display_name`: 新しい表示名, `intro`: 新しい自己紹介, `email`: 新しいメールアドレス, `show_email`: メールアドレスを公開するかどうか, `pw_hash`: ハッシュ化された新しいパスワード, `comment_moderation`: 記事へのコメントを承認する方法
+    /// `expected_version`: 更新の前提となるバージョン（`None`の場合は確認しない）
     /// このメソッドは、ユーザー名の重複チェックを行う必要があります。
-    /// `display_name`, `intro`, `email`, `show_email`, `password`のいずれかがNoneの場合は、そのフィールドは更新しません。
+    /// `display_name`, `intro`, `email`, `show_email`, `pw_hash`, `comment_moderation`のいずれかがNoneの場合は、そのフィールドは更新しません。
+    /// バージョンの確認と更新は不可分に行い、更新のたびにバージョンを1増やす
     /// # Errors
-    /// ユーザーが存在しない場合や、データベースへのアクセスに失敗した場合は`Err`を返す
+    /// ユーザーが存在しない場合や、バージョンが`expected_version`と異なる場合、データベースへのアクセスに失敗した場合は`Err`を返す
+    #[allow(clippy::too_many_arguments)]
     async fn update_user(
         &self,
         id: UserId,
@@ -60,15 +76,28 @@ pub trait UserRepository {
         intro: Option<String>,
         email: Option<String>,
         show_email: Option<bool>,
-        pw_hash: Option<Vec<u8>>,
+        pw_hash: Option<PasswordHash>,
+        comment_moderation: Option<CommentModeration>,
+        expected_version: Option<u32>,
     ) -> Result<User, UserServiceError>;
 
+    /// ユーザーの権限を変更する
+    /// `id`: ユーザーのObjectId, `role`: 新しい権限
+    /// バージョンを1増やし、更新が成功した場合は`Ok(User)`
+    /// # Errors
+    /// ユーザーが存在しない場合や、データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn update_role(&self, id: UserId, role: UserRole) -> Result<User, UserServiceError>;
+
     /// ユーザーを削除する
-    /// `id`: ユーザーのObjectId
+    /// `id`: ユーザーのObjectId, `expected_version`: 削除の前提となるバージョン（`None`の場合は確認しない）
     /// 更新が成功した場合は`Ok(())`
     /// # Errors
-    /// ユーザーが存在しない場合や、データベースへのアクセスに失敗した場合は`Err`を返す
-    async fn delete_user(&self, id: UserId) -> Result<(), UserServiceError>;
+    /// ユーザーが存在しない場合や、バージョンが`expected_version`と異なる場合、データベースへのアクセスに失敗した場合は`Err`を返す
+    async fn delete_user(
+        &self,
+        id: UserId,
+        expected_version: Option<u32>,
+    ) -> Result<(), UserServiceError>;
 
     /// ユーザー名が存在するかどうかをチェックし、存在しなかったときに`name`の型を`UserName`に変換して返す
     /// `name`: UserNameに変換するユーザー名
@@ -0,0 +1,57 @@
+use argon2::{
+    Algorithm, Argon2, Params, Version,
+    password_hash::{self, PasswordHasher as _, PasswordVerifier, SaltString, rand_core::OsRng},
+};
+
+use crate::domain::models::password_hasher::{PasswordHasher, PasswordHasherError};
+
+/// Argon2idでパスワードをハッシュ化する`PasswordHasher`
+/// パラメータを指定しない場合は`argon2`クレートの推奨値を使用する
+#[derive(Clone, Debug, Default)]
+pub struct Argon2PasswordHasher {
+    params: Params,
+}
+
+impl Argon2PasswordHasher {
+    pub fn new(params: Params) -> Self {
+        Self { params }
+    }
+
+    fn argon2(&self) -> Argon2<'static> {
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, self.params.clone())
+    }
+}
+
+impl PasswordHasher for Argon2PasswordHasher {
+    fn hash(&self, password: &str) -> Result<String, PasswordHasherError> {
+        let salt = SaltString::generate(&mut OsRng);
+        self.argon2()
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|e| PasswordHasherError(e.to_string()))
+    }
+
+    fn verify(&self, password: &str, phc: &str) -> bool {
+        password_hash::PasswordHash::new(phc).is_ok_and(|hash| {
+            self.argon2()
+                .verify_password(password.as_bytes(), &hash)
+                .is_ok()
+        })
+    }
+
+    fn needs_rehash(&self, phc: &str) -> bool {
+        let Ok(hash) = password_hash::PasswordHash::new(phc) else {
+            return true;
+        };
+        if hash.algorithm != Algorithm::Argon2id.ident()
+            || hash.version != Some(Version::V0x13.into())
+        {
+            return true;
+        }
+        !Params::try_from(&hash).is_ok_and(|params| {
+            params.m_cost() == self.params.m_cost()
+                && params.t_cost() == self.params.t_cost()
+                && params.p_cost() == self.params.p_cost()
+        })
+    }
+}
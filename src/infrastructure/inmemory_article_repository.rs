@@ -1,17 +1,25 @@
 use std::{
-    collections::HashMap,
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
     sync::{Arc, RwLock},
 };
 
 use async_trait::async_trait;
-use chrono::Utc;
+use bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
 use itertools::Itertools;
 
 use crate::domain::{
     models::{
-        article::{Article, ArticleId},
-        article_query::ArticleQuery,
+        article::{Article, ArticleId, Visibility},
+        article_query::{ArticleQuery, ArticleSort},
+        article_revision::ArticleRevision,
         article_service::ArticleServiceError,
+        author_stats::AuthorArticleStats,
+        page_cursor::PageCursor,
+        reaction::ReactionKind,
+        search::SearchDocumentCache,
+        tag::TagCount,
         user_name::UserName,
     },
     repositorys::article_repository::ArticleRepository,
@@ -20,6 +28,69 @@ use crate::domain::{
 #[derive(Clone, Default, Debug)]
 pub struct InMemoryArticleRepository {
     articles: Arc<RwLock<HashMap<ArticleId, Article>>>,
+    /// 記事ごとの版　`articles`のロックを取得した後にロックする
+    revisions: Arc<RwLock<HashMap<ArticleId, Vec<ArticleRevision>>>>,
+    /// ユーザーが付けたリアクション　`articles`のロックを取得した後にロックする
+    reactions: Arc<RwLock<HashSet<(ArticleId, UserName, ReactionKind)>>>,
+    /// 全文検索に使用する記事ごとの索引
+    search_documents: SearchDocumentCache,
+}
+
+/// 記事が`viewer`の一覧に表示され、クエリの条件をすべて満たすかどうか
+fn matches_query(article: &Article, query: &ArticleQuery, viewer: Option<&UserName>) -> bool {
+    article.is_listed_for(viewer)
+        && query
+            .title
+            .as_ref()
+            .is_none_or(|title| article.title.to_lowercase().contains(&title.to_lowercase()))
+        && query
+            .title_regex
+            .as_ref()
+            .is_none_or(|title_regex| title_regex.is_match(&article.title))
+        && query
+            .author
+            .as_ref()
+            .is_none_or(|author| article.author.as_str() == author)
+        && query.tags.iter().all(|tag| article.tags.contains(tag))
+        && (query.any_tags.is_empty()
+            || query.any_tags.iter().any(|tag| article.tags.contains(tag)))
+}
+
+/// `author`の`id`以外の記事が、現在または過去に`slug`を使用しているかどうか
+/// `MongodbArticleRepository`の一意インデックスと同じ制約を確認する
+fn is_slug_taken(
+    articles: &HashMap<ArticleId, Article>,
+    author: &UserName,
+    slug: &str,
+    id: Option<ArticleId>,
+) -> bool {
+    articles.values().any(|article| {
+        Some(article.id) != id
+            && article.author == *author
+            && article.slug_history.iter().any(|used| used == slug)
+    })
+}
+
+/// `sort`の並び順で記事を比較する
+/// `MongodbArticleRepository`と同じ順序になるように、値が同じ場合はIDで比較する
+fn compare_articles(sort: ArticleSort, a: &Article, b: &Article) -> Ordering {
+    let (a_id, b_id) = (ObjectId::from(a.id), ObjectId::from(b.id));
+    match sort {
+        ArticleSort::Newest => (b.created_at, b_id).cmp(&(a.created_at, a_id)),
+        ArticleSort::Oldest => (a.created_at, a_id).cmp(&(b.created_at, b_id)),
+        ArticleSort::Updated => (b.updated_at, b_id).cmp(&(a.updated_at, a_id)),
+        ArticleSort::Title => (&a.title, a_id).cmp(&(&b.title, b_id)),
+    }
+}
+
+/// `sort`の並び順で、記事がカーソルより後にあるかどうか
+/// カーソルに対応していない並び順では常に`true`を返す
+fn is_past_cursor(sort: ArticleSort, cursor: &PageCursor, article: &Article) -> bool {
+    match sort {
+        ArticleSort::Newest => cursor.is_before(article.created_at, article.id),
+        ArticleSort::Oldest => cursor.is_after(article.created_at, article.id),
+        ArticleSort::Updated | ArticleSort::Title => true,
+    }
 }
 
 #[async_trait]
@@ -28,11 +99,16 @@ impl ArticleRepository for InMemoryArticleRepository {
         &self,
         skip: usize,
         limit: usize,
+        sort: ArticleSort,
+        cursor: Option<PageCursor>,
+        viewer: Option<&UserName>,
     ) -> Result<Vec<Article>, ArticleServiceError> {
         let articles = self.articles.read().unwrap();
         let articles = articles
             .values()
-            .k_smallest_by_key(skip + limit, |article| article.created_at)
+            .filter(|article| article.is_listed_for(viewer))
+            .filter(|article| cursor.is_none_or(|cursor| is_past_cursor(sort, &cursor, article)))
+            .k_smallest_by(skip + limit, |a, b| compare_articles(sort, a, b))
             .skip(skip)
             .cloned()
             .collect();
@@ -45,14 +121,43 @@ impl ArticleRepository for InMemoryArticleRepository {
             .cloned()
             .ok_or_else(|| ArticleServiceError::ArticleNotFound)
     }
+    async fn get_article_by_slug(
+        &self,
+        author: &str,
+        slug: &str,
+    ) -> Result<Article, ArticleServiceError> {
+        let articles = self.articles.read().unwrap();
+        articles
+            .values()
+            .find(|article| {
+                article.author.as_str() == author
+                    && article.slug_history.iter().any(|used| used == slug)
+            })
+            .cloned()
+            .ok_or(ArticleServiceError::ArticleNotFound)
+    }
     async fn add_article(
         &self,
+        id: ArticleId,
         title: String,
+        slug: String,
         author: UserName,
         content: String,
+        visibility: Visibility,
+        publish_at: Option<DateTime<Utc>>,
+        tags: Vec<String>,
     ) -> Result<Article, ArticleServiceError> {
         let mut articles = self.articles.write().unwrap();
-        let article = Article::new_article(title, author, content);
+        if is_slug_taken(&articles, &author, &slug, None) {
+            return Err(ArticleServiceError::ArticleAlreadyExists);
+        }
+        let article = Article::new_article(
+            id, title, slug, author, content, visibility, publish_at, tags,
+        );
+        self.revisions
+            .write()
+            .unwrap()
+            .insert(article.id, vec![ArticleRevision::from_article(&article)]);
         articles.insert(article.id, article.clone());
         Ok(article)
     }
@@ -60,52 +165,591 @@ impl ArticleRepository for InMemoryArticleRepository {
         &self,
         id: ArticleId,
         title: Option<String>,
+        slug: Option<String>,
         content: Option<String>,
+        visibility: Option<Visibility>,
+        publish_at: Option<Option<DateTime<Utc>>>,
+        tags: Option<Vec<String>>,
+        expected_version: Option<u32>,
     ) -> Result<Article, ArticleServiceError> {
         let mut articles = self.articles.write().unwrap();
-        let article = articles
-            .get_mut(&id)
-            .ok_or_else(|| ArticleServiceError::ArticleNotFound)?;
+        let author = articles
+            .get(&id)
+            .ok_or_else(|| ArticleServiceError::ArticleNotFound)?
+            .author
+            .clone();
+        if let Some(new_slug) = &slug
+            && is_slug_taken(&articles, &author, new_slug, Some(id))
+        {
+            return Err(ArticleServiceError::ArticleAlreadyExists);
+        }
+        let article = articles.get_mut(&id).unwrap();
+        if expected_version.is_some_and(|version| version != article.version) {
+            return Err(ArticleServiceError::VersionMismatch);
+        }
+        let revised = title.is_some() || content.is_some();
+        let mut revisions = self.revisions.write().unwrap();
+        let article_revisions = revisions.entry(id).or_default();
+        // 版が導入される前の記事は、更新前の内容を最初の版として残す
+        if revised && article.revision == 0 {
+            article.revision = 1;
+            article_revisions.push(ArticleRevision::from_article(article));
+        }
         if let Some(new_title) = title {
             article.title = new_title;
         }
+        if let Some(new_slug) = slug {
+            if !article.slug_history.contains(&new_slug) {
+                article.slug_history.push(new_slug.clone());
+            }
+            article.slug = new_slug;
+        }
         if let Some(new_content) = content {
             article.content = new_content;
         }
+        if let Some(new_visibility) = visibility {
+            article.visibility = new_visibility;
+        }
+        if let Some(new_publish_at) = publish_at {
+            article.publish_at = new_publish_at;
+        }
+        if let Some(new_tags) = tags {
+            article.tags = new_tags;
+        }
         article.updated_at = Utc::now();
+        article.version += 1;
+        if revised {
+            article.revision += 1;
+            article_revisions.push(ArticleRevision::from_article(article));
+        }
         Ok(article.clone())
     }
-    async fn delete_article(&self, id: ArticleId) -> Result<(), ArticleServiceError> {
+    async fn get_articles_without_slug(&self) -> Result<Vec<Article>, ArticleServiceError> {
+        let articles = self.articles.read().unwrap();
+        Ok(articles
+            .values()
+            .filter(|article| article.slug.is_empty())
+            .cloned()
+            .collect())
+    }
+    async fn assign_slug(&self, id: ArticleId, slug: String) -> Result<(), ArticleServiceError> {
         let mut articles = self.articles.write().unwrap();
-        if articles.remove(&id).is_some() {
-            Ok(())
-        } else {
-            Err(ArticleServiceError::ArticleNotFound)
+        let author = articles
+            .get(&id)
+            .ok_or_else(|| ArticleServiceError::ArticleNotFound)?
+            .author
+            .clone();
+        if is_slug_taken(&articles, &author, &slug, Some(id)) {
+            return Err(ArticleServiceError::ArticleAlreadyExists);
+        }
+        let article = articles.get_mut(&id).unwrap();
+        if article.slug.is_empty() {
+            article.slug_history.push(slug.clone());
+            article.slug = slug;
+        }
+        Ok(())
+    }
+    async fn delete_article(
+        &self,
+        id: ArticleId,
+        expected_version: Option<u32>,
+    ) -> Result<(), ArticleServiceError> {
+        let mut articles = self.articles.write().unwrap();
+        let article = articles
+            .get(&id)
+            .ok_or_else(|| ArticleServiceError::ArticleNotFound)?;
+        if expected_version.is_some_and(|version| version != article.version) {
+            return Err(ArticleServiceError::VersionMismatch);
         }
+        articles.remove(&id);
+        self.search_documents.remove(id);
+        self.reactions
+            .write()
+            .unwrap()
+            .retain(|(article_id, _, _)| *article_id != id);
+        Ok(())
     }
     async fn get_articles_with_query(
         &self,
         skip: usize,
         limit: usize,
         query: ArticleQuery,
+        viewer: Option<&UserName>,
     ) -> Result<Vec<Article>, ArticleServiceError> {
         let articles = self.articles.read().unwrap();
         let filtered_articles: Vec<Article> = articles
             .values()
-            .filter(|article| {
-                query
-                    .title
-                    .as_ref()
-                    .is_none_or(|title| article.title.contains(title))
-                    && query
-                        .author
-                        .as_ref()
-                        .is_none_or(|author| article.author.as_str() == author)
+            .filter(|article| matches_query(article, &query, viewer))
+            .k_smallest_by(skip + limit, |a, b| compare_articles(query.sort, a, b))
+            .skip(skip)
+            .cloned()
+            .collect();
+        Ok(filtered_articles)
+    }
+    async fn search_articles_by_terms(
+        &self,
+        q: &str,
+        terms: &[String],
+        query: ArticleQuery,
+        skip: usize,
+        limit: usize,
+        viewer: Option<&UserName>,
+    ) -> Result<Vec<(f64, Article)>, ArticleServiceError> {
+        let articles = self.articles.read().unwrap();
+        let scored: Vec<(f64, Article)> = articles
+            .values()
+            .filter(|article| matches_query(article, &query, viewer))
+            .filter_map(|article| {
+                let document = self.search_documents.get(article);
+                document
+                    .contains_all(terms)
+                    .then(|| (document.relevance(q, terms), article))
+            })
+            .sorted_by(|(a_score, a), (b_score, b)| {
+                b_score
+                    .total_cmp(a_score)
+                    .then_with(|| compare_articles(ArticleSort::Newest, a, b))
             })
+            .skip(skip)
+            .take(limit)
+            .map(|(score, article)| (score, article.clone()))
+            .collect();
+        Ok(scored)
+    }
+    async fn count_articles_by_terms(
+        &self,
+        terms: &[String],
+        query: ArticleQuery,
+        viewer: Option<&UserName>,
+    ) -> Result<u64, ArticleServiceError> {
+        let articles = self.articles.read().unwrap();
+        let count = articles
+            .values()
+            .filter(|article| matches_query(article, &query, viewer))
+            .filter(|article| self.search_documents.get(article).contains_all(terms))
+            .count();
+        Ok(count as u64)
+    }
+    async fn count_articles(&self, viewer: Option<&UserName>) -> Result<u64, ArticleServiceError> {
+        let articles = self.articles.read().unwrap();
+        let count = articles
+            .values()
+            .filter(|article| article.is_listed_for(viewer))
+            .count();
+        Ok(count as u64)
+    }
+    async fn count_articles_with_query(
+        &self,
+        query: ArticleQuery,
+        viewer: Option<&UserName>,
+    ) -> Result<u64, ArticleServiceError> {
+        let articles = self.articles.read().unwrap();
+        let count = articles
+            .values()
+            .filter(|article| matches_query(article, &query, viewer))
+            .count();
+        Ok(count as u64)
+    }
+    async fn get_articles_by_author(
+        &self,
+        author: &str,
+        skip: usize,
+        limit: usize,
+        sort: ArticleSort,
+        cursor: Option<PageCursor>,
+        viewer: Option<&UserName>,
+    ) -> Result<Vec<Article>, ArticleServiceError> {
+        let articles = self.articles.read().unwrap();
+        let articles = articles
+            .values()
+            .filter(|article| article.author.as_str() == author && article.is_listed_for(viewer))
+            .filter(|article| cursor.is_none_or(|cursor| is_past_cursor(sort, &cursor, article)))
+            .k_smallest_by(skip + limit, |a, b| compare_articles(sort, a, b))
+            .skip(skip)
+            .cloned()
+            .collect();
+        Ok(articles)
+    }
+    async fn count_articles_by_author(
+        &self,
+        author: &str,
+        viewer: Option<&UserName>,
+    ) -> Result<u64, ArticleServiceError> {
+        let articles = self.articles.read().unwrap();
+        let count = articles
+            .values()
+            .filter(|article| article.author.as_str() == author && article.is_listed_for(viewer))
+            .count();
+        Ok(count as u64)
+    }
+    async fn get_all_articles_by_author(
+        &self,
+        author: &UserName,
+    ) -> Result<Vec<Article>, ArticleServiceError> {
+        let articles = self.articles.read().unwrap();
+        Ok(articles
+            .values()
+            .filter(|article| article.author == *author)
+            .cloned()
+            .collect())
+    }
+    async fn count_all_articles_by_author(
+        &self,
+        author: &UserName,
+    ) -> Result<u64, ArticleServiceError> {
+        let articles = self.articles.read().unwrap();
+        let count = articles
+            .values()
+            .filter(|article| article.author == *author)
+            .count();
+        Ok(count as u64)
+    }
+    async fn reassign_article(
+        &self,
+        id: ArticleId,
+        author: UserName,
+        slug: String,
+    ) -> Result<Article, ArticleServiceError> {
+        let mut articles = self.articles.write().unwrap();
+        if is_slug_taken(&articles, &author, &slug, Some(id)) {
+            return Err(ArticleServiceError::ArticleAlreadyExists);
+        }
+        let article = articles
+            .get_mut(&id)
+            .ok_or(ArticleServiceError::ArticleNotFound)?;
+        article.author = author;
+        article.slug_history = vec![slug.clone()];
+        article.slug = slug;
+        article.version += 1;
+        Ok(article.clone())
+    }
+    async fn get_author_stats(
+        &self,
+        author: &str,
+        viewer: Option<&UserName>,
+    ) -> Result<AuthorArticleStats, ArticleServiceError> {
+        let articles = self.articles.read().unwrap();
+        let mut stats = AuthorArticleStats::default();
+        for article in articles
+            .values()
+            .filter(|article| article.author.as_str() == author && article.is_listed_for(viewer))
+        {
+            stats.article_count += 1;
+            stats.first_posted_at = Some(
+                stats
+                    .first_posted_at
+                    .map_or(article.created_at, |first| first.min(article.created_at)),
+            );
+            stats.last_posted_at = Some(
+                stats
+                    .last_posted_at
+                    .map_or(article.created_at, |last| last.max(article.created_at)),
+            );
+            stats.total_reactions += article.reactions.values().sum::<u64>();
+            stats.article_ids.push(article.id);
+        }
+        Ok(stats)
+    }
+    async fn get_drafts(
+        &self,
+        author: &UserName,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Vec<Article>, ArticleServiceError> {
+        let articles = self.articles.read().unwrap();
+        let drafts = articles
+            .values()
+            .filter(|article| article.visibility == Visibility::Draft && &article.author == author)
             .k_smallest_by_key(skip + limit, |article| article.created_at)
             .skip(skip)
             .cloned()
             .collect();
-        Ok(filtered_articles)
+        Ok(drafts)
+    }
+    async fn publish_due_articles(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Article>, ArticleServiceError> {
+        let mut articles = self.articles.write().unwrap();
+        let published = articles
+            .values_mut()
+            .filter(|article| article.is_due(now))
+            .sorted_by_key(|article| article.publish_at)
+            .map(|article| {
+                article.visibility = Visibility::Published;
+                article.publish_at = None;
+                article.updated_at = now;
+                article.version += 1;
+                article.clone()
+            })
+            .collect();
+        Ok(published)
+    }
+    async fn get_revisions(
+        &self,
+        id: ArticleId,
+    ) -> Result<Vec<ArticleRevision>, ArticleServiceError> {
+        let revisions = self.revisions.read().unwrap();
+        Ok(revisions.get(&id).cloned().unwrap_or_default())
+    }
+    async fn get_revision(
+        &self,
+        id: ArticleId,
+        rev: u32,
+    ) -> Result<ArticleRevision, ArticleServiceError> {
+        let revisions = self.revisions.read().unwrap();
+        revisions
+            .get(&id)
+            .and_then(|revisions| revisions.iter().find(|revision| revision.rev == rev))
+            .cloned()
+            .ok_or_else(|| ArticleServiceError::RevisionNotFound)
+    }
+    async fn get_tag_counts(&self) -> Result<Vec<TagCount>, ArticleServiceError> {
+        let articles = self.articles.read().unwrap();
+        let tag_counts = articles
+            .values()
+            .filter(|article| article.is_listed_for(None))
+            .flat_map(|article| article.tags.iter())
+            .counts()
+            .into_iter()
+            .map(|(tag, count)| TagCount {
+                tag: tag.clone(),
+                count: count as u64,
+            })
+            .sorted_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)))
+            .collect();
+        Ok(tag_counts)
+    }
+    async fn get_articles_by_authors(
+        &self,
+        authors: &[UserName],
+        cursor: Option<PageCursor>,
+        limit: usize,
+        viewer: Option<&UserName>,
+    ) -> Result<Vec<Article>, ArticleServiceError> {
+        let articles = self.articles.read().unwrap();
+        let articles = articles
+            .values()
+            .filter(|article| authors.contains(&article.author) && article.is_listed_for(viewer))
+            .filter(|article| {
+                cursor.is_none_or(|cursor| cursor.is_before(article.created_at, article.id))
+            })
+            .k_largest_by_key(limit, |article| {
+                (article.created_at, ObjectId::from(article.id))
+            })
+            .cloned()
+            .collect();
+        Ok(articles)
+    }
+    async fn add_reaction(
+        &self,
+        id: ArticleId,
+        user: UserName,
+        kind: ReactionKind,
+    ) -> Result<Article, ArticleServiceError> {
+        // 記事の書き込みロックを保持したまま、リアクションの記録と数の更新を行う
+        let mut articles = self.articles.write().unwrap();
+        let article = articles
+            .get_mut(&id)
+            .ok_or_else(|| ArticleServiceError::ArticleNotFound)?;
+        if self.reactions.write().unwrap().insert((id, user, kind)) {
+            *article.reactions.entry(kind).or_default() += 1;
+            article.reacted_at = Some(Utc::now());
+        }
+        Ok(article.clone())
+    }
+    async fn remove_reaction(
+        &self,
+        id: ArticleId,
+        user: &UserName,
+        kind: ReactionKind,
+    ) -> Result<Article, ArticleServiceError> {
+        let mut articles = self.articles.write().unwrap();
+        let article = articles
+            .get_mut(&id)
+            .ok_or_else(|| ArticleServiceError::ArticleNotFound)?;
+        if self
+            .reactions
+            .write()
+            .unwrap()
+            .remove(&(id, user.clone(), kind))
+        {
+            if let Some(count) = article.reactions.get_mut(&kind) {
+                *count = count.saturating_sub(1);
+                if *count == 0 {
+                    article.reactions.remove(&kind);
+                }
+            }
+            article.reacted_at = Some(Utc::now());
+        }
+        Ok(article.clone())
+    }
+    async fn get_user_reactions(
+        &self,
+        ids: &[ArticleId],
+        user: &UserName,
+    ) -> Result<HashMap<ArticleId, Vec<ReactionKind>>, ArticleServiceError> {
+        let reactions = self.reactions.read().unwrap();
+        let mut user_reactions = reactions
+            .iter()
+            .filter(|(article_id, reacted_by, _)| reacted_by == user && ids.contains(article_id))
+            .map(|(article_id, _, kind)| (*article_id, *kind))
+            .into_group_map();
+        for kinds in user_reactions.values_mut() {
+            kinds.sort();
+        }
+        Ok(user_reactions)
+    }
+    async fn get_reactions_by_user(
+        &self,
+        user: &UserName,
+    ) -> Result<Vec<(ArticleId, ReactionKind)>, ArticleServiceError> {
+        let reactions = self.reactions.read().unwrap();
+        Ok(reactions
+            .iter()
+            .filter(|(_, reacted_by, _)| reacted_by == user)
+            .map(|(article_id, _, kind)| (*article_id, *kind))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn legacy_article_keeps_its_content_as_first_revision() {
+        let repository = InMemoryArticleRepository::default();
+        // 版が導入される前に作成された記事
+        let mut article = Article::new_article(
+            ArticleId::new(),
+            "タイトル".to_string(),
+            "title".to_string(),
+            UserName::new("furakuta".to_string()),
+            "元の本文".to_string(),
+            Visibility::Published,
+            None,
+            Vec::new(),
+        );
+        article.revision = 0;
+        repository
+            .articles
+            .write()
+            .unwrap()
+            .insert(article.id, article.clone());
+
+        // バージョンが一致しない更新では版を記録しない
+        let stale = repository
+            .update_article(
+                article.id,
+                None,
+                None,
+                Some("古い本文".to_string()),
+                None,
+                None,
+                None,
+                Some(article.version + 1),
+            )
+            .await;
+        assert!(matches!(stale, Err(ArticleServiceError::VersionMismatch)));
+        assert!(
+            repository
+                .get_revisions(article.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let updated = repository
+            .update_article(
+                article.id,
+                None,
+                None,
+                Some("新しい本文".to_string()),
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(updated.revision, 2);
+        let revisions = repository.get_revisions(article.id).await.unwrap();
+        let contents: Vec<_> = revisions
+            .iter()
+            .map(|revision| (revision.rev, revision.content.as_str()))
+            .collect();
+        assert_eq!(contents, [(1, "元の本文"), (2, "新しい本文")]);
+
+        // 記事を削除しても版は残る
+        repository.delete_article(article.id, None).await.unwrap();
+        assert_eq!(repository.get_revisions(article.id).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn full_text_search_ranks_every_match() {
+        let repository = InMemoryArticleRepository::default();
+        let author = UserName::new("furakuta".to_string());
+        let mut ids = Vec::new();
+        for (i, content) in ["rust rust rust", "rust", "rust", "python"]
+            .into_iter()
+            .enumerate()
+        {
+            let article = repository
+                .add_article(
+                    ArticleId::new(),
+                    format!("記事{i}"),
+                    format!("article-{i}"),
+                    author.clone(),
+                    content.to_string(),
+                    Visibility::Published,
+                    None,
+                    Vec::new(),
+                )
+                .await
+                .unwrap();
+            ids.push(article.id);
+        }
+        let terms = ["rust".to_string()];
+        let search = |skip, limit| {
+            repository.search_articles_by_terms(
+                "rust",
+                &terms,
+                ArticleQuery::default(),
+                skip,
+                limit,
+                None,
+            )
+        };
+
+        // 最も古い記事でも関連度が高ければ先頭に並び、総数は一致するすべての記事を数える
+        let hits = search(0, 1).await.unwrap();
+        assert_eq!(hits[0].1.id, ids[0]);
+        let count = repository
+            .count_articles_by_terms(&terms, ArticleQuery::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(count, 3);
+        // 関連度が同じ記事は新しい順に並ぶ
+        let hits = search(1, 10).await.unwrap();
+        let rest: Vec<_> = hits.iter().map(|(_, article)| article.id).collect();
+        assert_eq!(rest, [ids[2], ids[1]]);
+
+        // 更新された記事は索引を作り直す
+        repository
+            .update_article(
+                ids[0],
+                None,
+                None,
+                Some("python".to_string()),
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        let hits = search(0, 10).await.unwrap();
+        assert!(hits.iter().all(|(_, article)| article.id != ids[0]));
     }
 }
@@ -0,0 +1,188 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use itertools::Itertools;
+
+use crate::domain::{
+    models::{
+        article::ArticleId,
+        comment::{Comment, CommentId, CommentStatus},
+        comment_service::CommentServiceError,
+        user_name::UserName,
+    },
+    repositorys::comment_repository::CommentRepository,
+};
+
+#[derive(Clone, Default, Debug)]
+pub struct InMemoryCommentRepository {
+    comments: Arc<RwLock<HashMap<CommentId, Comment>>>,
+}
+
+#[async_trait]
+impl CommentRepository for InMemoryCommentRepository {
+    async fn get_comments_by_article(
+        &self,
+        article_id: ArticleId,
+    ) -> Result<Vec<Comment>, CommentServiceError> {
+        let comments = self.comments.read().unwrap();
+        let mut comments: Vec<Comment> = comments
+            .values()
+            .filter(|comment| comment.article_id == article_id)
+            .cloned()
+            .collect();
+        comments.sort_by_key(|comment| comment.created_at);
+        Ok(comments)
+    }
+    async fn get_comment_by_id(&self, id: CommentId) -> Result<Comment, CommentServiceError> {
+        let comments = self.comments.read().unwrap();
+        comments
+            .get(&id)
+            .cloned()
+            .ok_or(CommentServiceError::CommentNotFound)
+    }
+    async fn add_comment(
+        &self,
+        article_id: ArticleId,
+        article_author: UserName,
+        parent_id: Option<CommentId>,
+        author: UserName,
+        content: String,
+        status: CommentStatus,
+    ) -> Result<Comment, CommentServiceError> {
+        let mut comments = self.comments.write().unwrap();
+        let comment = Comment::new_comment(
+            article_id,
+            article_author,
+            parent_id,
+            author,
+            content,
+            status,
+        );
+        comments.insert(comment.id, comment.clone());
+        Ok(comment)
+    }
+    async fn update_comment(
+        &self,
+        id: CommentId,
+        content: String,
+    ) -> Result<Comment, CommentServiceError> {
+        let mut comments = self.comments.write().unwrap();
+        let comment = comments
+            .get_mut(&id)
+            .ok_or(CommentServiceError::CommentNotFound)?;
+        comment.content = content;
+        comment.updated_at = Utc::now();
+        Ok(comment.clone())
+    }
+    async fn delete_comment(&self, id: CommentId) -> Result<(), CommentServiceError> {
+        let mut comments = self.comments.write().unwrap();
+        let comment = comments
+            .get_mut(&id)
+            .ok_or(CommentServiceError::CommentNotFound)?;
+        comment.deleted = true;
+        comment.content = String::new();
+        comment.updated_at = Utc::now();
+        Ok(())
+    }
+    async fn get_pending_comments(
+        &self,
+        article_author: &UserName,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Vec<Comment>, CommentServiceError> {
+        let comments = self.comments.read().unwrap();
+        let comments = comments
+            .values()
+            .filter(|comment| {
+                comment.status == CommentStatus::Pending
+                    && !comment.deleted
+                    && comment.article_author.as_ref() == Some(article_author)
+            })
+            .k_smallest_by_key(skip + limit, |comment| comment.created_at)
+            .skip(skip)
+            .cloned()
+            .collect();
+        Ok(comments)
+    }
+    async fn update_comment_status(
+        &self,
+        id: CommentId,
+        status: CommentStatus,
+    ) -> Result<Comment, CommentServiceError> {
+        let mut comments = self.comments.write().unwrap();
+        let comment = comments
+            .get_mut(&id)
+            .ok_or(CommentServiceError::CommentNotFound)?;
+        comment.status = status;
+        comment.updated_at = Utc::now();
+        Ok(comment.clone())
+    }
+    async fn delete_comments_by_article(
+        &self,
+        article_id: ArticleId,
+    ) -> Result<(), CommentServiceError> {
+        let mut comments = self.comments.write().unwrap();
+        comments.retain(|_, comment| comment.article_id != article_id);
+        Ok(())
+    }
+    async fn reassign_article_author(
+        &self,
+        from: &UserName,
+        to: &UserName,
+    ) -> Result<(), CommentServiceError> {
+        let mut comments = self.comments.write().unwrap();
+        for comment in comments.values_mut() {
+            if comment.article_author.as_ref() == Some(from) {
+                comment.article_author = Some(to.clone());
+            }
+        }
+        Ok(())
+    }
+    async fn reassign_comment_author(
+        &self,
+        from: &UserName,
+        to: &UserName,
+    ) -> Result<(), CommentServiceError> {
+        let mut comments = self.comments.write().unwrap();
+        for comment in comments.values_mut() {
+            if &comment.author == from {
+                comment.author = to.clone();
+            }
+        }
+        Ok(())
+    }
+    async fn delete_comments_by_author(
+        &self,
+        author: &UserName,
+    ) -> Result<(), CommentServiceError> {
+        let mut comments = self.comments.write().unwrap();
+        let now = Utc::now();
+        for comment in comments.values_mut() {
+            if &comment.author == author && !comment.deleted {
+                comment.deleted = true;
+                comment.content = String::new();
+                comment.updated_at = now;
+            }
+        }
+        Ok(())
+    }
+    async fn count_comments_by_articles(
+        &self,
+        article_ids: &[ArticleId],
+    ) -> Result<u64, CommentServiceError> {
+        let comments = self.comments.read().unwrap();
+        let count = comments
+            .values()
+            .filter(|comment| {
+                comment.status == CommentStatus::Approved
+                    && !comment.deleted
+                    && article_ids.contains(&comment.article_id)
+            })
+            .count();
+        Ok(count as u64)
+    }
+}
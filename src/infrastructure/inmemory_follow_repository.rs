@@ -0,0 +1,92 @@
+use std::sync::{Arc, RwLock};
+
+use async_trait::async_trait;
+
+use crate::domain::{
+    models::{follow::Follow, follow_service::FollowServiceError, user_name::UserName},
+    repositorys::follow_repository::FollowRepository,
+};
+
+#[derive(Clone, Default, Debug)]
+pub struct InMemoryFollowRepository {
+    /// フォローした順に並ぶ
+    follows: Arc<RwLock<Vec<Follow>>>,
+}
+
+#[async_trait]
+impl FollowRepository for InMemoryFollowRepository {
+    async fn add_follow(
+        &self,
+        follower: &UserName,
+        followee: &UserName,
+    ) -> Result<(), FollowServiceError> {
+        let mut follows = self.follows.write().unwrap();
+        if !follows
+            .iter()
+            .any(|follow| &follow.follower == follower && &follow.followee == followee)
+        {
+            follows.push(Follow::new(follower.clone(), followee.clone()));
+        }
+        Ok(())
+    }
+    async fn remove_follow(
+        &self,
+        follower: &UserName,
+        followee: &UserName,
+    ) -> Result<(), FollowServiceError> {
+        let mut follows = self.follows.write().unwrap();
+        follows.retain(|follow| !(&follow.follower == follower && &follow.followee == followee));
+        Ok(())
+    }
+    async fn remove_follows_of(&self, user: &UserName) -> Result<(), FollowServiceError> {
+        let mut follows = self.follows.write().unwrap();
+        follows.retain(|follow| &follow.follower != user && &follow.followee != user);
+        Ok(())
+    }
+    async fn get_followers(
+        &self,
+        followee: &UserName,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Vec<Follow>, FollowServiceError> {
+        let follows = self.follows.read().unwrap();
+        let followers = follows
+            .iter()
+            .rev()
+            .filter(|follow| &follow.followee == followee)
+            .skip(skip)
+            .take(limit)
+            .cloned()
+            .collect();
+        Ok(followers)
+    }
+    async fn get_following(
+        &self,
+        follower: &UserName,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Vec<Follow>, FollowServiceError> {
+        let follows = self.follows.read().unwrap();
+        let following = follows
+            .iter()
+            .rev()
+            .filter(|follow| &follow.follower == follower)
+            .skip(skip)
+            .take(limit)
+            .cloned()
+            .collect();
+        Ok(following)
+    }
+    async fn get_followee_names(
+        &self,
+        follower: &UserName,
+    ) -> Result<Vec<UserName>, FollowServiceError> {
+        let follows = self.follows.read().unwrap();
+        let names = follows
+            .iter()
+            .filter(|follow| &follow.follower == follower)
+            .map(|follow| follow.followee.clone())
+            .collect();
+        Ok(names)
+    }
+}
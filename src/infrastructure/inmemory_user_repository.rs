@@ -1,4 +1,6 @@
 use async_trait::async_trait;
+use bson::oid::ObjectId;
+use itertools::Itertools;
 use std::{
     collections::HashMap,
     sync::{Arc, RwLock},
@@ -6,7 +8,9 @@ use std::{
 
 use crate::domain::{
     models::{
-        user::{User, UserId},
+        page_cursor::PageCursor,
+        password_hasher::PasswordHash,
+        user::{CommentModeration, User, UserId, UserRole},
         user_name::UserName,
         user_service::UserServiceError,
     },
@@ -20,9 +24,25 @@ pub struct InMemoryUserRepository {
 
 #[async_trait]
 impl UserRepository for InMemoryUserRepository {
-    async fn get_users(&self, skip: usize, limit: usize) -> Result<Vec<User>, UserServiceError> {
+    async fn get_users(
+        &self,
+        skip: usize,
+        limit: usize,
+        cursor: Option<PageCursor>,
+    ) -> Result<Vec<User>, UserServiceError> {
         let users = self.users.read().unwrap();
-        Ok(users.values().skip(skip).take(limit).cloned().collect())
+        Ok(users
+            .values()
+            .filter(|user| cursor.is_none_or(|cursor| cursor.is_after(user.created_at, user.id)))
+            .k_smallest_by_key(skip + limit, |user| {
+                (user.created_at, ObjectId::from(user.id))
+            })
+            .skip(skip)
+            .cloned()
+            .collect())
+    }
+    async fn count_users(&self) -> Result<u64, UserServiceError> {
+        Ok(self.users.read().unwrap().len() as u64)
     }
     async fn get_user_by_id(&self, id: UserId) -> Result<User, UserServiceError> {
         let users = self.users.read().unwrap();
@@ -46,7 +66,7 @@ impl UserRepository for InMemoryUserRepository {
         intro: String,
         email: String,
         show_email: bool,
-        pw_hash: Vec<u8>,
+        pw_hash: PasswordHash,
     ) -> Result<User, UserServiceError> {
         let mut users = self.users.write().unwrap();
         let user_name = validate_user_name(&users, name)?;
@@ -60,6 +80,10 @@ impl UserRepository for InMemoryUserRepository {
             show_email,
             pw_hash,
             created_at: chrono::Utc::now(),
+            updated_at: None,
+            role: UserRole::default(),
+            comment_moderation: CommentModeration::default(),
+            version: 1,
         };
         users.insert(id, user.clone());
         Ok(user)
@@ -72,7 +96,9 @@ impl UserRepository for InMemoryUserRepository {
         intro: Option<String>,
         email: Option<String>,
         show_email: Option<bool>,
-        pw_hash: Option<Vec<u8>>,
+        pw_hash: Option<PasswordHash>,
+        comment_moderation: Option<CommentModeration>,
+        expected_version: Option<u32>,
     ) -> Result<User, UserServiceError> {
         let mut users = self.users.write().unwrap();
         let validated_name = name
@@ -82,6 +108,9 @@ impl UserRepository for InMemoryUserRepository {
         let user = users
             .get_mut(&id)
             .ok_or_else(|| UserServiceError::UserNotFound)?;
+        if expected_version.is_some_and(|version| version != user.version) {
+            return Err(UserServiceError::VersionMismatch);
+        }
 
         if let Some(valid_name) = validated_name {
             user.name = valid_name;
@@ -101,15 +130,37 @@ impl UserRepository for InMemoryUserRepository {
         if let Some(new_password) = pw_hash {
             user.pw_hash = new_password;
         }
+        if let Some(new_comment_moderation) = comment_moderation {
+            user.comment_moderation = new_comment_moderation;
+        }
+        user.updated_at = Some(chrono::Utc::now());
+        user.version += 1;
+        Ok(user.clone())
+    }
+    async fn update_role(&self, id: UserId, role: UserRole) -> Result<User, UserServiceError> {
+        let mut users = self.users.write().unwrap();
+        let user = users
+            .get_mut(&id)
+            .ok_or_else(|| UserServiceError::UserNotFound)?;
+        user.role = role;
+        user.updated_at = Some(chrono::Utc::now());
+        user.version += 1;
         Ok(user.clone())
     }
-    async fn delete_user(&self, id: UserId) -> Result<(), UserServiceError> {
+    async fn delete_user(
+        &self,
+        id: UserId,
+        expected_version: Option<u32>,
+    ) -> Result<(), UserServiceError> {
         let mut users = self.users.write().unwrap();
-        if users.remove(&id).is_some() {
-            Ok(())
-        } else {
-            Err(UserServiceError::UserNotFound)
+        let user = users
+            .get(&id)
+            .ok_or_else(|| UserServiceError::UserNotFound)?;
+        if expected_version.is_some_and(|version| version != user.version) {
+            return Err(UserServiceError::VersionMismatch);
         }
+        users.remove(&id);
+        Ok(())
     }
     async fn validate_user_name(&self, name: &str) -> Result<UserName, UserServiceError> {
         let users = self.users.read().unwrap();
@@ -121,6 +172,9 @@ fn validate_user_name(
     users: &HashMap<UserId, User>,
     name: String,
 ) -> Result<UserName, UserServiceError> {
+    if UserName::is_reserved(&name) {
+        return Err(UserServiceError::ReservedUserName);
+    }
     //ユーザー名が重複していた場合はエラー
     if users.values().any(|user| user.name.as_str() == name) {
         Err(UserServiceError::UserAlreadyExists)
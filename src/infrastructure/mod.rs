@@ -1,5 +1,13 @@
+pub mod argon2_password_hasher;
 pub mod inmemory_article_repository;
+pub mod inmemory_comment_repository;
+pub mod inmemory_follow_repository;
 pub mod inmemory_user_repository;
 pub mod mongo_article_repository;
 pub mod mongo_client;
+pub mod mongo_comment_repository;
+pub mod mongo_cursor;
+pub mod mongo_follow_repository;
+pub mod mongo_session_store;
 pub mod mongo_user_repository;
+pub mod mongo_version;
@@ -1,34 +1,402 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
 use futures::TryStreamExt;
 use mongodb::{
-    Collection, Database,
-    bson::{Document, doc},
+    Collection, Database, IndexModel,
+    bson::{Bson, Document, doc},
+    error::{ErrorKind, WriteFailure},
+    options::{IndexOptions, ReturnDocument},
 };
 
 use async_trait::async_trait;
 
 use crate::domain::{
     models::{
-        article::{Article, ArticleId},
-        article_query::ArticleQuery,
+        article::{Article, ArticleId, Visibility},
+        article_query::{ArticleQuery, ArticleSort},
+        article_revision::ArticleRevision,
         article_service::ArticleServiceError,
+        author_stats::AuthorArticleStats,
+        page_cursor::PageCursor,
+        reaction::ReactionKind,
+        search::{PHRASE_BONUS, TF_SATURATION, TITLE_WEIGHT, index_terms, normalize},
+        tag::TagCount,
+        title_regex::TITLE_REGEX_MAX_TIME,
         user_name::UserName,
     },
     repositorys::article_repository::ArticleRepository,
 };
+use crate::infrastructure::{
+    mongo_cursor::{
+        CREATED_AT_DATE, after_cursor_filter, before_cursor_filter, fill_missing_created_at_dates,
+        to_bson_date,
+    },
+    mongo_version::insert_version_condition,
+};
 
 #[derive(Clone, Debug)]
 pub struct MongodbArticleRepository {
-    database: Database,
     collection: Collection<Document>,
+    /// 記事の版を追記するコレクション
+    revisions: Collection<Document>,
+    /// ユーザーが付けたリアクションを記録するコレクション
+    reactions: Collection<Document>,
 }
 
 impl MongodbArticleRepository {
     pub fn new(database: Database) -> Self {
         let collection: Collection<Document> = database.collection("articles");
+        let revisions: Collection<Document> = database.collection("article_revisions");
+        let reactions: Collection<Document> = database.collection("article_reactions");
         Self {
-            database,
             collection,
+            revisions,
+            reactions,
+        }
+    }
+
+    /// 記事のコレクションに必要なインデックスを作成する
+    /// 一覧や予約投稿、タグ、全文検索、タイムラインの検索に使用するインデックスと、記事ごとに版番号を一意にするインデックス、
+    /// 著者ごとにスラッグを一意にするインデックス、ユーザーが同じ種類のリアクションを重複して付けられないようにするインデックスを含む
+    ///
+    /// # Errors
+    /// インデックスの作成に失敗した場合は`Err`を返す
+    pub async fn create_indexes(&self) -> Result<(), mongodb::error::Error> {
+        let index = IndexModel::builder()
+            .keys(doc! {"visibility": 1, PUBLISH_AT_DATE: 1})
+            .build();
+        self.collection.create_index(index).await?;
+
+        // `tags`は配列のため、マルチキーインデックスになる
+        let tag_index = IndexModel::builder().keys(doc! {"tags": 1}).build();
+        self.collection.create_index(tag_index).await?;
+        let search_index = IndexModel::builder().keys(doc! {SEARCH_TERMS: 1}).build();
+        self.collection.create_index(search_index).await?;
+
+        // 一覧を指定した並び順で取得するために使用する
+        let created_index = IndexModel::builder()
+            .keys(doc! {CREATED_AT_DATE: 1, "_id": 1})
+            .build();
+        self.collection.create_index(created_index).await?;
+        let updated_index = IndexModel::builder()
+            .keys(doc! {"updated_at": -1, "_id": -1})
+            .build();
+        self.collection.create_index(updated_index).await?;
+        let title_index = IndexModel::builder()
+            .keys(doc! {"title": 1, "_id": 1})
+            .build();
+        self.collection.create_index(title_index).await?;
+
+        let revision_index = IndexModel::builder()
+            .keys(doc! {"article_id": 1, "rev": 1})
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+        self.revisions.create_index(revision_index).await?;
+
+        // タイムラインで著者ごとに新しい順に取得するために使用する
+        let author_index = IndexModel::builder()
+            .keys(doc! {"author.inner": 1, CREATED_AT_DATE: -1, "_id": -1})
+            .build();
+        self.collection.create_index(author_index).await?;
+
+        // 著者ごとにスラッグを一意にする　過去のスラッグも転送に使用するため、履歴全体で一意にする
+        // スラッグが割り当てられる前の記事は履歴を持たないため、インデックスに含めない
+        let slug_index = IndexModel::builder()
+            .keys(doc! {"author.inner": 1, "slug_history": 1})
+            .options(
+                IndexOptions::builder()
+                    .unique(true)
+                    .partial_filter_expression(doc! {"slug_history": {"$exists": true}})
+                    .build(),
+            )
+            .build();
+        self.collection.create_index(slug_index).await?;
+
+        let reaction_index = IndexModel::builder()
+            .keys(doc! {"article_id": 1, "user": 1, "kind": 1})
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+        self.reactions.create_index(reaction_index).await?;
+        Ok(())
+    }
+
+    /// 作成日時を日付型で保持する前に作成された記事に、作成日時を登録する
+    /// 登録した記事の数を返す
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    pub async fn fill_missing_created_at_dates(&self) -> Result<u64, mongodb::error::Error> {
+        fill_missing_created_at_dates(&self.collection).await
+    }
+
+    /// 検索語や正規化したタイトル・本文が登録されていないか、以前の正規化で登録された記事に、それらを登録する
+    /// 全文検索やデータベースでの関連度の計算の導入前に作成され、その後更新されていない記事を検索できるようにする
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    pub async fn index_missing_search_terms(&self) -> Result<(), ArticleServiceError> {
+        let mut cursor = self
+            .collection
+            .find(doc! {SEARCH_INDEX_VERSION: {"$ne": CURRENT_SEARCH_INDEX_VERSION}})
+            .await?;
+        while let Some(doc) = cursor.try_next().await? {
+            if let Ok(article) = bson::from_document::<Article>(doc) {
+                self.index_search_terms(&article).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 記事のタイトルと本文から抽出した検索語と、正規化したタイトル・本文を登録する
+    /// 更新が競合した場合に古い内容で上書きしないよう、バージョンが変わっていない場合のみ登録する
+    async fn index_search_terms(&self, article: &Article) -> Result<(), ArticleServiceError> {
+        self.collection
+            .update_one(
+                doc! {"_id": bson::to_bson(&article.id).unwrap(), "version": article.version},
+                doc! {"$set": search_fields(article)},
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn add_revision(&self, article: &Article) -> Result<(), ArticleServiceError> {
+        let revision_doc = bson::to_document(&ArticleRevision::from_article(article)).unwrap();
+        self.revisions.insert_one(revision_doc).await?;
+        Ok(())
+    }
+
+    /// 版が導入される前の記事（`revision`が`0`か存在しない）の現在の内容を、最初の版として記録する
+    /// 更新で失われないように、タイトルか本文を更新する前に呼び出す
+    /// 版番号の変更は不可分に行うため、並行して呼び出しても記録するのは1回のみ
+    /// `expected_version`が一致しない場合は、続く更新も失敗するため記録しない
+    async fn record_first_revision(
+        &self,
+        id: ArticleId,
+        expected_version: Option<u32>,
+    ) -> Result<(), ArticleServiceError> {
+        let mut filter = doc! {
+            "_id": bson::to_bson(&id).unwrap(),
+            "revision": {"$in": [0, Bson::Null]},
+        };
+        insert_version_condition(&mut filter, expected_version);
+        let Some(doc) = self
+            .collection
+            .find_one_and_update(filter, doc! {"$set": {"revision": 1}})
+            .await?
+        else {
+            return Ok(());
+        };
+        let mut article = bson::from_document::<Article>(doc)
+            .map_err(|_| ArticleServiceError::ArticleNotFound)?;
+        article.revision = 1;
+        self.add_revision(&article).await
+    }
+
+    /// 条件付きの更新・削除が対象を見つけられなかった場合に、その理由を返す
+    /// 記事自体は存在する場合は、バージョンが一致しなかったものとみなす
+    async fn missing_reason(&self, id: ArticleId) -> ArticleServiceError {
+        let filter = doc! { "_id": bson::to_bson(&id).unwrap() };
+        match self.collection.find_one(filter).await {
+            Ok(Some(_)) => ArticleServiceError::VersionMismatch,
+            Ok(None) => ArticleServiceError::ArticleNotFound,
+            Err(e) => e.into(),
+        }
+    }
+}
+
+fn reaction_doc(id: ArticleId, user: &UserName, kind: ReactionKind) -> Document {
+    doc! {
+        "article_id": bson::to_bson(&id).unwrap(),
+        "user": user.as_str(),
+        "kind": kind.as_str(),
+    }
+}
+
+/// 一意インデックスに違反したかどうか
+/// `find_one_and_update`はコマンドのエラーとして返すため、両方を確認する
+fn is_duplicate_key(error: &mongodb::error::Error) -> bool {
+    match error.kind.as_ref() {
+        ErrorKind::Write(WriteFailure::WriteError(write_error)) => write_error.code == 11000,
+        ErrorKind::Command(command_error) => command_error.code == 11000,
+        _ => false,
+    }
+}
+
+/// `max_time`を超えて打ち切られた場合は`SearchTimeout`に変換する
+fn search_timeout(error: mongodb::error::Error) -> ArticleServiceError {
+    match error.kind.as_ref() {
+        // MaxTimeMSExpired
+        ErrorKind::Command(command_error) if command_error.code == 50 => {
+            ArticleServiceError::SearchTimeout
         }
+        _ => error.into(),
+    }
+}
+
+/// スラッグの一意インデックスに違反した場合は`ArticleAlreadyExists`に変換する
+fn slug_conflict(error: mongodb::error::Error) -> ArticleServiceError {
+    if is_duplicate_key(&error) {
+        ArticleServiceError::ArticleAlreadyExists
+    } else {
+        error.into()
+    }
+}
+
+/// スラッグを持たない記事に絞り込むフィルタ
+fn missing_slug_filter() -> Document {
+    doc! {"$or": [{"slug": {"$exists": false}}, {"slug": ""}]}
+}
+
+/// 予約投稿の日時をBSONの日付型で保持するフィールド
+/// `Article::publish_at`は文字列として保存されるため、日時での比較やインデックスにはこちらを使用する
+const PUBLISH_AT_DATE: &str = "publish_at_date";
+
+/// 全文検索に使用する検索語を保持するフィールド
+/// `search::index_terms`でタイトルと本文から抽出し、`Article`には含めない
+const SEARCH_TERMS: &str = "search_terms";
+
+/// 関連度の計算に使用する、正規化したタイトルと本文を保持するフィールド
+/// `search::normalize`で正規化し、`Article`には含めない
+const SEARCH_TITLE: &str = "search_title";
+const SEARCH_CONTENT: &str = "search_content";
+
+/// 全文検索に使用するフィールドを登録した際の正規化の版を保持するフィールド
+/// 正規化の方法を変更した場合は`CURRENT_SEARCH_INDEX_VERSION`を上げ、起動時に登録し直す
+const SEARCH_INDEX_VERSION: &str = "search_index_version";
+/// 2: 文字ごとではなく文字列全体をNFKC正規化する
+const CURRENT_SEARCH_INDEX_VERSION: i32 = 2;
+
+/// 全文検索に使用するフィールドの値
+fn search_fields(article: &Article) -> Document {
+    let terms: Vec<String> = index_terms(&article.title, &article.content)
+        .into_iter()
+        .collect();
+    doc! {
+        SEARCH_TERMS: terms,
+        SEARCH_TITLE: normalize(&article.title),
+        SEARCH_CONTENT: normalize(&article.content),
+        SEARCH_INDEX_VERSION: CURRENT_SEARCH_INDEX_VERSION,
+    }
+}
+
+/// 記事の関連度を計算する集計式
+/// `SearchDocument::relevance`と同じ計算を、保存した正規化済みのタイトルと本文に対して行う
+/// 出現回数は、検索語で分割した数から1を引いて数える（`str::matches`と同じく重ならない出現を数える）
+fn relevance_expression(q: &str, terms: &[String]) -> Document {
+    let saturate = |field: &str, term: &str| {
+        let tf = doc! {"$subtract": [
+            {"$size": {"$split": [{"$ifNull": [format!("${field}"), ""]}, term]}},
+            1,
+        ]};
+        doc! {"$divide": [
+            {"$multiply": [tf.clone(), TF_SATURATION + 1.0]},
+            {"$add": [tf, TF_SATURATION]},
+        ]}
+    };
+    let mut scores: Vec<Bson> = terms
+        .iter()
+        .map(|term| {
+            Bson::Document(doc! {"$add": [
+                {"$multiply": [saturate(SEARCH_TITLE, term), TITLE_WEIGHT]},
+                saturate(SEARCH_CONTENT, term),
+            ]})
+        })
+        .collect();
+
+    let phrase = normalize(q.trim());
+    if !phrase.is_empty() {
+        let contains = |field: &str| {
+            doc! {"$gte": [
+                {"$indexOfCP": [{"$ifNull": [format!("${field}"), ""]}, phrase.as_str()]},
+                0,
+            ]}
+        };
+        scores.push(Bson::Document(doc! {"$cond": [
+            contains(SEARCH_TITLE),
+            TITLE_WEIGHT * PHRASE_BONUS,
+            0.0,
+        ]}));
+        scores.push(Bson::Document(doc! {"$cond": [
+            contains(SEARCH_CONTENT),
+            PHRASE_BONUS,
+            0.0,
+        ]}));
+    }
+    doc! {"$add": scores}
+}
+
+/// `viewer`に対して一覧に表示される記事に絞り込むフィルタ
+/// `Article::is_listed_for`と同じ条件をクエリで表現する
+/// `visibility`を持たない古い記事は公開記事として扱う
+fn listed_filter(viewer: Option<&UserName>) -> Document {
+    let mut conditions = vec![
+        doc! {"visibility": bson::to_bson(&Visibility::Published).unwrap()},
+        doc! {"visibility": {"$exists": false}},
+    ];
+    if let Some(viewer) = viewer {
+        conditions.push(doc! {
+            "author.inner": viewer.as_str(),
+            "visibility": {"$ne": bson::to_bson(&Visibility::Draft).unwrap()},
+        });
+    }
+    doc! {"$or": conditions}
+}
+
+/// `author`の記事のうち、`viewer`に対して一覧に表示されるものに絞り込むフィルタ
+fn author_filter(author: &str, viewer: Option<&UserName>) -> Document {
+    doc! {"$and": [listed_filter(viewer), {"author.inner": author}]}
+}
+
+/// `viewer`に対して一覧に表示され、クエリの条件をすべて満たす記事に絞り込むフィルタ
+fn query_filter(query: &ArticleQuery, viewer: Option<&UserName>) -> Document {
+    let mut filter = listed_filter(viewer);
+    // 文字列は正規表現として解釈されないようにエスケープする
+    let mut title_conditions = Vec::new();
+    if let Some(title_query) = &query.title {
+        title_conditions
+            .push(doc! {"title": {"$regex": regex::escape(title_query), "$options": "i"}});
+    }
+    if let Some(title_regex) = &query.title_regex {
+        title_conditions.push(doc! {"title": {"$regex": title_regex.as_str()}});
+    }
+    if !title_conditions.is_empty() {
+        filter.insert("$and", title_conditions);
+    }
+    if let Some(author_query) = &query.author {
+        filter.insert("author.inner", author_query);
+    }
+    let mut tag_filter = doc! {};
+    if !query.tags.is_empty() {
+        tag_filter.insert("$all", &query.tags);
+    }
+    if !query.any_tags.is_empty() {
+        tag_filter.insert("$in", &query.any_tags);
+    }
+    if !tag_filter.is_empty() {
+        filter.insert("tags", tag_filter);
+    }
+    filter
+}
+
+/// `sort`の並び順を表すソート条件
+/// `InMemoryArticleRepository`と同じ順序になるように、値が同じ場合はIDで並べる
+fn sort_doc(sort: ArticleSort) -> Document {
+    match sort {
+        ArticleSort::Newest => doc! {CREATED_AT_DATE: -1, "_id": -1},
+        ArticleSort::Oldest => doc! {CREATED_AT_DATE: 1, "_id": 1},
+        ArticleSort::Updated => doc! {"updated_at": -1, "_id": -1},
+        ArticleSort::Title => doc! {"title": 1, "_id": 1},
+    }
+}
+
+/// `sort`の並び順でカーソルより後にある記事に絞り込むフィルタ
+/// カーソルに対応していない並び順では`None`を返す
+fn past_cursor_filter(sort: ArticleSort, cursor: &PageCursor) -> Option<Document> {
+    match sort {
+        ArticleSort::Newest => Some(before_cursor_filter(cursor)),
+        ArticleSort::Oldest => Some(after_cursor_filter(cursor)),
+        ArticleSort::Updated | ArticleSort::Title => None,
     }
 }
 
@@ -38,10 +406,18 @@ impl ArticleRepository for MongodbArticleRepository {
         &self,
         skip: usize,
         limit: usize,
+        sort: ArticleSort,
+        cursor: Option<PageCursor>,
+        viewer: Option<&UserName>,
     ) -> Result<Vec<Article>, ArticleServiceError> {
+        let mut filter = listed_filter(viewer);
+        if let Some(cursor_filter) = cursor.and_then(|cursor| past_cursor_filter(sort, &cursor)) {
+            filter = doc! {"$and": [filter, cursor_filter]};
+        }
         let mut cursor = self
             .collection
-            .find(doc! {})
+            .find(filter)
+            .sort(sort_doc(sort))
             .skip(skip as u64)
             .limit(limit as i64)
             .await?;
@@ -60,24 +436,54 @@ impl ArticleRepository for MongodbArticleRepository {
             "_id": bson::to_bson(&id).unwrap()
         };
 
-        if let Some(doc) = self.collection.find_one(filter).await? {
-            if let Ok(article) = bson::from_document::<Article>(doc) {
-                return Ok(article);
-            }
+        if let Some(doc) = self.collection.find_one(filter).await?
+            && let Ok(article) = bson::from_document::<Article>(doc)
+        {
+            return Ok(article);
         }
 
         Err(ArticleServiceError::ArticleNotFound)
     }
 
+    async fn get_article_by_slug(
+        &self,
+        author: &str,
+        slug: &str,
+    ) -> Result<Article, ArticleServiceError> {
+        let filter = doc! {"author.inner": author, "slug_history": slug};
+        if let Some(doc) = self.collection.find_one(filter).await?
+            && let Ok(article) = bson::from_document::<Article>(doc)
+        {
+            return Ok(article);
+        }
+        Err(ArticleServiceError::ArticleNotFound)
+    }
+
     async fn add_article(
         &self,
+        id: ArticleId,
         title: String,
+        slug: String,
         author: UserName,
         content: String,
+        visibility: Visibility,
+        publish_at: Option<DateTime<Utc>>,
+        tags: Vec<String>,
     ) -> Result<Article, ArticleServiceError> {
-        let article = Article::new_article(title, author, content);
-        let article_doc = bson::to_document(&article).unwrap();
-        self.collection.insert_one(article_doc).await?;
+        let article = Article::new_article(
+            id, title, slug, author, content, visibility, publish_at, tags,
+        );
+        let mut article_doc = bson::to_document(&article).unwrap();
+        article_doc.insert(CREATED_AT_DATE, to_bson_date(article.created_at));
+        if let Some(publish_at) = publish_at {
+            article_doc.insert(PUBLISH_AT_DATE, to_bson_date(publish_at));
+        }
+        article_doc.extend(search_fields(&article));
+        self.collection
+            .insert_one(article_doc)
+            .await
+            .map_err(slug_conflict)?;
+        self.add_revision(&article).await?;
         Ok(article)
     }
 
@@ -85,41 +491,116 @@ impl ArticleRepository for MongodbArticleRepository {
         &self,
         id: ArticleId,
         title: Option<String>,
+        slug: Option<String>,
         content: Option<String>,
+        visibility: Option<Visibility>,
+        publish_at: Option<Option<DateTime<Utc>>>,
+        tags: Option<Vec<String>>,
+        expected_version: Option<u32>,
     ) -> Result<Article, ArticleServiceError> {
-        let filter = doc! { "_id": bson::to_bson(&id).unwrap() };
+        let mut filter = doc! { "_id": bson::to_bson(&id).unwrap() };
+        insert_version_condition(&mut filter, expected_version);
 
-        let mut set_doc = doc! {};
+        // `$currentDate`はBSONの日付型で保存してしまい`Article`として読み込めなくなるため、
+        // 他の日時と同じ形式で保存する
+        let mut set_doc = doc! {"updated_at": bson::to_bson(&Utc::now()).unwrap()};
+        let revised = title.is_some() || content.is_some();
+        if revised {
+            self.record_first_revision(id, expected_version).await?;
+        }
         if let Some(new_title) = title {
             set_doc.insert("title", new_title);
         }
         if let Some(new_content) = content {
             set_doc.insert("content", new_content);
         }
+        if let Some(new_visibility) = visibility {
+            set_doc.insert("visibility", bson::to_bson(&new_visibility).unwrap());
+        }
+        if let Some(new_tags) = tags {
+            set_doc.insert("tags", new_tags);
+        }
 
-        let mut update_doc = doc! {"$currentDate": {"updated_at": true}};
-        if !set_doc.is_empty() {
-            update_doc.insert("$set", set_doc);
+        let mut update_doc = doc! {};
+        if let Some(new_slug) = slug {
+            set_doc.insert("slug", &new_slug);
+            update_doc.insert("$addToSet", doc! {"slug_history": new_slug});
+        }
+        match publish_at {
+            Some(Some(new_publish_at)) => {
+                set_doc.insert("publish_at", bson::to_bson(&new_publish_at).unwrap());
+                set_doc.insert(PUBLISH_AT_DATE, to_bson_date(new_publish_at));
+            }
+            Some(None) => {
+                update_doc.insert("$unset", doc! {"publish_at": "", PUBLISH_AT_DATE: ""});
+            }
+            None => {}
+        }
+        update_doc.insert("$set", set_doc);
+        if revised {
+            update_doc.insert("$inc", doc! {"version": 1, "revision": 1});
+        } else {
+            update_doc.insert("$inc", doc! {"version": 1});
         }
-        self.collection
-            .update_one(filter.clone(), update_doc)
-            .await?;
 
-        if let Some(doc) = self.collection.find_one(filter).await? {
+        // バージョンの確認と更新を不可分に行い、更新後の内容で版を記録する
+        let Some(doc) = self
+            .collection
+            .find_one_and_update(filter, update_doc)
+            .return_document(ReturnDocument::After)
+            .await
+            .map_err(slug_conflict)?
+        else {
+            return Err(self.missing_reason(id).await);
+        };
+        let article = bson::from_document::<Article>(doc)
+            .map_err(|_| ArticleServiceError::ArticleNotFound)?;
+        if revised {
+            self.add_revision(&article).await?;
+            self.index_search_terms(&article).await?;
+        }
+        Ok(article)
+    }
+
+    async fn get_articles_without_slug(&self) -> Result<Vec<Article>, ArticleServiceError> {
+        let mut cursor = self.collection.find(missing_slug_filter()).await?;
+        let mut articles = Vec::new();
+        while let Some(doc) = cursor.try_next().await? {
             if let Ok(article) = bson::from_document::<Article>(doc) {
-                return Ok(article);
+                articles.push(article);
             }
         }
-        Err(ArticleServiceError::ArticleNotFound)
+        Ok(articles)
     }
 
-    async fn delete_article(&self, id: ArticleId) -> Result<(), ArticleServiceError> {
-        let filter = doc! { "_id": bson::to_bson(&id).unwrap() };
+    async fn assign_slug(&self, id: ArticleId, slug: String) -> Result<(), ArticleServiceError> {
+        let mut filter = missing_slug_filter();
+        filter.insert("_id", bson::to_bson(&id).unwrap());
+        self.collection
+            .update_one(
+                filter,
+                doc! {"$set": {"slug": &slug}, "$addToSet": {"slug_history": &slug}},
+            )
+            .await
+            .map_err(slug_conflict)?;
+        Ok(())
+    }
+
+    async fn delete_article(
+        &self,
+        id: ArticleId,
+        expected_version: Option<u32>,
+    ) -> Result<(), ArticleServiceError> {
+        let mut filter = doc! { "_id": bson::to_bson(&id).unwrap() };
+        insert_version_condition(&mut filter, expected_version);
         let result = self.collection.delete_one(filter).await?;
         if result.deleted_count == 1 {
+            self.reactions
+                .delete_many(doc! {"article_id": bson::to_bson(&id).unwrap()})
+                .await?;
             Ok(())
         } else {
-            Err(ArticleServiceError::ArticleNotFound)
+            Err(self.missing_reason(id).await)
         }
     }
 
@@ -128,18 +609,261 @@ impl ArticleRepository for MongodbArticleRepository {
         skip: usize,
         limit: usize,
         query: ArticleQuery,
+        viewer: Option<&UserName>,
     ) -> Result<Vec<Article>, ArticleServiceError> {
-        let mut filter = doc! {};
-        if let Some(title_query) = query.title {
-            filter.insert("title", doc! {"$regex": title_query, "$options": "i"});
+        // 正規表現の評価に時間がかかりすぎる場合は打ち切る
+        let mut cursor = self
+            .collection
+            .find(query_filter(&query, viewer))
+            .sort(sort_doc(query.sort))
+            .skip(skip as u64)
+            .limit(limit as i64)
+            .max_time(TITLE_REGEX_MAX_TIME)
+            .await
+            .map_err(search_timeout)?;
+
+        let mut articles: Vec<Article> = Vec::new();
+        while let Some(doc) = cursor.try_next().await.map_err(search_timeout)? {
+            if let Ok(article) = bson::from_document::<Article>(doc) {
+                articles.push(article);
+            }
         }
-        if let Some(author_query) = query.author {
-            filter.insert("author.inner", author_query);
+        Ok(articles)
+    }
+
+    async fn search_articles_by_terms(
+        &self,
+        q: &str,
+        terms: &[String],
+        query: ArticleQuery,
+        skip: usize,
+        limit: usize,
+        viewer: Option<&UserName>,
+    ) -> Result<Vec<(f64, Article)>, ArticleServiceError> {
+        let mut filter = query_filter(&query, viewer);
+        filter.insert(SEARCH_TERMS, doc! {"$all": terms});
+        // 関連度が同じ記事は新しい順に並べる
+        let mut sort = doc! {"score": -1};
+        sort.extend(sort_doc(ArticleSort::Newest));
+        let pipeline = vec![
+            doc! {"$match": filter},
+            doc! {"$addFields": {"score": relevance_expression(q, terms)}},
+            doc! {"$sort": sort},
+            doc! {"$skip": skip as i64},
+            doc! {"$limit": limit as i64},
+            doc! {"$unset": [SEARCH_TERMS, SEARCH_TITLE, SEARCH_CONTENT]},
+        ];
+        let mut cursor = self
+            .collection
+            .aggregate(pipeline)
+            .max_time(TITLE_REGEX_MAX_TIME)
+            .await
+            .map_err(search_timeout)?;
+
+        let mut articles: Vec<(f64, Article)> = Vec::new();
+        while let Some(mut doc) = cursor.try_next().await.map_err(search_timeout)? {
+            let score = doc.remove("score").and_then(|score| score.as_f64());
+            if let (Some(score), Ok(article)) = (score, bson::from_document::<Article>(doc)) {
+                articles.push((score, article));
+            }
         }
+        Ok(articles)
+    }
+
+    async fn count_articles_by_terms(
+        &self,
+        terms: &[String],
+        query: ArticleQuery,
+        viewer: Option<&UserName>,
+    ) -> Result<u64, ArticleServiceError> {
+        let mut filter = query_filter(&query, viewer);
+        filter.insert(SEARCH_TERMS, doc! {"$all": terms});
+        self.collection
+            .count_documents(filter)
+            .max_time(TITLE_REGEX_MAX_TIME)
+            .await
+            .map_err(search_timeout)
+    }
+
+    async fn count_articles(&self, viewer: Option<&UserName>) -> Result<u64, ArticleServiceError> {
+        Ok(self
+            .collection
+            .count_documents(listed_filter(viewer))
+            .await?)
+    }
 
+    async fn count_articles_with_query(
+        &self,
+        query: ArticleQuery,
+        viewer: Option<&UserName>,
+    ) -> Result<u64, ArticleServiceError> {
+        self.collection
+            .count_documents(query_filter(&query, viewer))
+            .max_time(TITLE_REGEX_MAX_TIME)
+            .await
+            .map_err(search_timeout)
+    }
+
+    async fn get_articles_by_author(
+        &self,
+        author: &str,
+        skip: usize,
+        limit: usize,
+        sort: ArticleSort,
+        cursor: Option<PageCursor>,
+        viewer: Option<&UserName>,
+    ) -> Result<Vec<Article>, ArticleServiceError> {
+        let mut conditions = vec![author_filter(author, viewer)];
+        if let Some(cursor_filter) = cursor.and_then(|cursor| past_cursor_filter(sort, &cursor)) {
+            conditions.push(cursor_filter);
+        }
+        let mut cursor = self
+            .collection
+            .find(doc! {"$and": conditions})
+            .sort(sort_doc(sort))
+            .skip(skip as u64)
+            .limit(limit as i64)
+            .await?;
+
+        let mut articles: Vec<Article> = Vec::new();
+        while let Some(doc) = cursor.try_next().await? {
+            if let Ok(article) = bson::from_document::<Article>(doc) {
+                articles.push(article);
+            }
+        }
+        Ok(articles)
+    }
+
+    async fn count_articles_by_author(
+        &self,
+        author: &str,
+        viewer: Option<&UserName>,
+    ) -> Result<u64, ArticleServiceError> {
+        Ok(self
+            .collection
+            .count_documents(author_filter(author, viewer))
+            .await?)
+    }
+
+    async fn get_all_articles_by_author(
+        &self,
+        author: &UserName,
+    ) -> Result<Vec<Article>, ArticleServiceError> {
+        let mut cursor = self
+            .collection
+            .find(doc! {"author.inner": author.as_str()})
+            .await?;
+        let mut articles = Vec::new();
+        while let Some(doc) = cursor.try_next().await? {
+            if let Ok(article) = bson::from_document::<Article>(doc) {
+                articles.push(article);
+            }
+        }
+        Ok(articles)
+    }
+
+    async fn count_all_articles_by_author(
+        &self,
+        author: &UserName,
+    ) -> Result<u64, ArticleServiceError> {
+        Ok(self
+            .collection
+            .count_documents(doc! {"author.inner": author.as_str()})
+            .await?)
+    }
+
+    async fn reassign_article(
+        &self,
+        id: ArticleId,
+        author: UserName,
+        slug: String,
+    ) -> Result<Article, ArticleServiceError> {
+        let update = doc! {
+            "$set": {
+                "author": bson::to_bson(&author).unwrap(),
+                "slug": &slug,
+                "slug_history": [&slug],
+            },
+            "$inc": {"version": 1},
+        };
+        let Some(doc) = self
+            .collection
+            .find_one_and_update(doc! {"_id": bson::to_bson(&id).unwrap()}, update)
+            .return_document(ReturnDocument::After)
+            .await
+            .map_err(slug_conflict)?
+        else {
+            return Err(ArticleServiceError::ArticleNotFound);
+        };
+        bson::from_document::<Article>(doc).map_err(|_| ArticleServiceError::ArticleNotFound)
+    }
+
+    async fn get_author_stats(
+        &self,
+        author: &str,
+        viewer: Option<&UserName>,
+    ) -> Result<AuthorArticleStats, ArticleServiceError> {
+        // `created_at`は同じ形式の文字列として保存されているため、文字列の最小・最大が最初・最後の日時になる
+        // リアクションの数は種類ごとのフィールドに保持されているため、配列に変換して合計する
+        let pipeline = vec![
+            doc! {"$match": author_filter(author, viewer)},
+            doc! {"$group": {
+                "_id": Bson::Null,
+                "article_count": {"$sum": 1},
+                "first_posted_at": {"$min": "$created_at"},
+                "last_posted_at": {"$max": "$created_at"},
+                "total_reactions": {"$sum": {"$sum": {"$map": {
+                    "input": {"$objectToArray": {"$ifNull": ["$reactions", {}]}},
+                    "in": "$$this.v",
+                }}}},
+                "article_ids": {"$push": "$_id"},
+            }},
+        ];
+        let mut cursor = self.collection.aggregate(pipeline).await?;
+
+        // 記事が1つもない場合は結果のドキュメントが返らない
+        let Some(doc) = cursor.try_next().await? else {
+            return Ok(AuthorArticleStats::default());
+        };
+        let count = |key: &str| match doc.get(key) {
+            Some(Bson::Int32(count)) => *count as u64,
+            Some(Bson::Int64(count)) => *count as u64,
+            _ => 0,
+        };
+        let date = |key: &str| {
+            doc.get(key)
+                .and_then(|date| bson::from_bson::<DateTime<Utc>>(date.clone()).ok())
+        };
+        Ok(AuthorArticleStats {
+            article_count: count("article_count"),
+            first_posted_at: date("first_posted_at"),
+            last_posted_at: date("last_posted_at"),
+            total_reactions: count("total_reactions"),
+            article_ids: doc
+                .get_array("article_ids")
+                .map(|ids| {
+                    ids.iter()
+                        .filter_map(|id| bson::from_bson::<ArticleId>(id.clone()).ok())
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+    }
+
+    async fn get_drafts(
+        &self,
+        author: &UserName,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Vec<Article>, ArticleServiceError> {
+        let filter = doc! {
+            "author.inner": author.as_str(),
+            "visibility": bson::to_bson(&Visibility::Draft).unwrap(),
+        };
         let mut cursor = self
             .collection
             .find(filter)
+            .sort(doc! {CREATED_AT_DATE: 1, "_id": 1})
             .skip(skip as u64)
             .limit(limit as i64)
             .await?;
@@ -152,4 +876,243 @@ impl ArticleRepository for MongodbArticleRepository {
         }
         Ok(articles)
     }
+
+    async fn publish_due_articles(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Article>, ArticleServiceError> {
+        let filter = doc! {
+            "visibility": bson::to_bson(&Visibility::Draft).unwrap(),
+            PUBLISH_AT_DATE: {"$lte": to_bson_date(now)},
+        };
+        let update = doc! {
+            "$set": {
+                "visibility": bson::to_bson(&Visibility::Published).unwrap(),
+                "updated_at": bson::to_bson(&now).unwrap(),
+            },
+            "$unset": {"publish_at": "", PUBLISH_AT_DATE: ""},
+            "$inc": {"version": 1},
+        };
+
+        // 複数のインスタンスが同時に実行しても同じ記事を二重に公開しないよう、1件ずつ不可分に更新する
+        let mut articles: Vec<Article> = Vec::new();
+        while let Some(doc) = self
+            .collection
+            .find_one_and_update(filter.clone(), update.clone())
+            .sort(doc! {PUBLISH_AT_DATE: 1})
+            .return_document(ReturnDocument::After)
+            .await?
+        {
+            if let Ok(article) = bson::from_document::<Article>(doc) {
+                articles.push(article);
+            }
+        }
+        Ok(articles)
+    }
+
+    async fn get_revisions(
+        &self,
+        id: ArticleId,
+    ) -> Result<Vec<ArticleRevision>, ArticleServiceError> {
+        let mut cursor = self
+            .revisions
+            .find(doc! {"article_id": bson::to_bson(&id).unwrap()})
+            .sort(doc! {"rev": 1})
+            .await?;
+
+        let mut revisions: Vec<ArticleRevision> = Vec::new();
+        while let Some(doc) = cursor.try_next().await? {
+            if let Ok(revision) = bson::from_document::<ArticleRevision>(doc) {
+                revisions.push(revision);
+            }
+        }
+        Ok(revisions)
+    }
+
+    async fn get_revision(
+        &self,
+        id: ArticleId,
+        rev: u32,
+    ) -> Result<ArticleRevision, ArticleServiceError> {
+        let filter = doc! {
+            "article_id": bson::to_bson(&id).unwrap(),
+            "rev": rev,
+        };
+
+        if let Some(doc) = self.revisions.find_one(filter).await?
+            && let Ok(revision) = bson::from_document::<ArticleRevision>(doc)
+        {
+            return Ok(revision);
+        }
+        Err(ArticleServiceError::RevisionNotFound)
+    }
+
+    async fn get_tag_counts(&self) -> Result<Vec<TagCount>, ArticleServiceError> {
+        let pipeline = vec![
+            doc! {"$match": listed_filter(None)},
+            doc! {"$unwind": "$tags"},
+            doc! {"$group": {"_id": "$tags", "count": {"$sum": 1}}},
+            doc! {"$sort": {"count": -1, "_id": 1}},
+        ];
+        let mut cursor = self.collection.aggregate(pipeline).await?;
+
+        let mut tag_counts: Vec<TagCount> = Vec::new();
+        while let Some(doc) = cursor.try_next().await? {
+            if let Ok(tag) = doc.get_str("_id") {
+                tag_counts.push(TagCount {
+                    tag: tag.to_string(),
+                    count: match doc.get("count") {
+                        Some(Bson::Int32(count)) => *count as u64,
+                        Some(Bson::Int64(count)) => *count as u64,
+                        _ => 0,
+                    },
+                });
+            }
+        }
+        Ok(tag_counts)
+    }
+
+    async fn get_articles_by_authors(
+        &self,
+        authors: &[UserName],
+        cursor: Option<PageCursor>,
+        limit: usize,
+        viewer: Option<&UserName>,
+    ) -> Result<Vec<Article>, ArticleServiceError> {
+        let author_names: Vec<&str> = authors.iter().map(UserName::as_str).collect();
+        let mut conditions = vec![
+            listed_filter(viewer),
+            doc! {"author.inner": {"$in": author_names}},
+        ];
+        if let Some(cursor) = cursor {
+            conditions.push(before_cursor_filter(&cursor));
+        }
+        let mut cursor = self
+            .collection
+            .find(doc! {"$and": conditions})
+            .sort(doc! {CREATED_AT_DATE: -1, "_id": -1})
+            .limit(limit as i64)
+            .await?;
+
+        let mut articles: Vec<Article> = Vec::new();
+        while let Some(doc) = cursor.try_next().await? {
+            if let Ok(article) = bson::from_document::<Article>(doc) {
+                articles.push(article);
+            }
+        }
+        Ok(articles)
+    }
+
+    async fn add_reaction(
+        &self,
+        id: ArticleId,
+        user: UserName,
+        kind: ReactionKind,
+    ) -> Result<Article, ArticleServiceError> {
+        // 存在しない記事へのリアクションが記録されないように、先に記事を確認する
+        self.get_article_by_id(id).await?;
+        // 一意インデックスにより、同じ種類のリアクションは一度しか記録されない
+        match self
+            .reactions
+            .insert_one(reaction_doc(id, &user, kind))
+            .await
+        {
+            Ok(_) => {}
+            Err(e) if is_duplicate_key(&e) => return self.get_article_by_id(id).await,
+            Err(e) => return Err(e.into()),
+        }
+
+        let count_field = format!("reactions.{kind}");
+        let update = doc! {
+            "$inc": {count_field.as_str(): 1},
+            "$set": {"reacted_at": bson::to_bson(&Utc::now()).unwrap()},
+        };
+        let Some(doc) = self
+            .collection
+            .find_one_and_update(doc! {"_id": bson::to_bson(&id).unwrap()}, update)
+            .return_document(ReturnDocument::After)
+            .await?
+        else {
+            // 確認の後に記事が削除された場合は、記録したリアクションを取り消す
+            self.reactions
+                .delete_one(reaction_doc(id, &user, kind))
+                .await?;
+            return Err(ArticleServiceError::ArticleNotFound);
+        };
+        bson::from_document::<Article>(doc).map_err(|_| ArticleServiceError::ArticleNotFound)
+    }
+
+    async fn remove_reaction(
+        &self,
+        id: ArticleId,
+        user: &UserName,
+        kind: ReactionKind,
+    ) -> Result<Article, ArticleServiceError> {
+        let result = self
+            .reactions
+            .delete_one(reaction_doc(id, user, kind))
+            .await?;
+        if result.deleted_count == 0 {
+            return self.get_article_by_id(id).await;
+        }
+
+        let count_field = format!("reactions.{kind}");
+        let filter = doc! {"_id": bson::to_bson(&id).unwrap()};
+        let update = doc! {
+            "$inc": {count_field.as_str(): -1},
+            "$set": {"reacted_at": bson::to_bson(&Utc::now()).unwrap()},
+        };
+        self.collection.update_one(filter.clone(), update).await?;
+        // 数が0になった種類を取り除く　間にリアクションが付けられた場合は条件に一致しないため取り除かれない
+        let mut empty_filter = filter;
+        empty_filter.insert(count_field.as_str(), doc! {"$lte": 0});
+        self.collection
+            .update_one(empty_filter, doc! {"$unset": {count_field.as_str(): ""}})
+            .await?;
+        self.get_article_by_id(id).await
+    }
+
+    async fn get_user_reactions(
+        &self,
+        ids: &[ArticleId],
+        user: &UserName,
+    ) -> Result<HashMap<ArticleId, Vec<ReactionKind>>, ArticleServiceError> {
+        let filter = doc! {
+            "article_id": {"$in": bson::to_bson(ids).unwrap()},
+            "user": user.as_str(),
+        };
+        let mut cursor = self.reactions.find(filter).await?;
+
+        let mut user_reactions: HashMap<ArticleId, Vec<ReactionKind>> = HashMap::new();
+        while let Some(doc) = cursor.try_next().await? {
+            if let (Some(article_id), Some(kind)) = (doc.get("article_id"), doc.get("kind"))
+                && let Ok(article_id) = bson::from_bson::<ArticleId>(article_id.clone())
+                && let Ok(kind) = bson::from_bson::<ReactionKind>(kind.clone())
+            {
+                user_reactions.entry(article_id).or_default().push(kind);
+            }
+        }
+        for kinds in user_reactions.values_mut() {
+            kinds.sort();
+        }
+        Ok(user_reactions)
+    }
+
+    async fn get_reactions_by_user(
+        &self,
+        user: &UserName,
+    ) -> Result<Vec<(ArticleId, ReactionKind)>, ArticleServiceError> {
+        let mut cursor = self.reactions.find(doc! {"user": user.as_str()}).await?;
+
+        let mut user_reactions = Vec::new();
+        while let Some(doc) = cursor.try_next().await? {
+            if let (Some(article_id), Some(kind)) = (doc.get("article_id"), doc.get("kind"))
+                && let Ok(article_id) = bson::from_bson::<ArticleId>(article_id.clone())
+                && let Ok(kind) = bson::from_bson::<ReactionKind>(kind.clone())
+            {
+                user_reactions.push((article_id, kind));
+            }
+        }
+        Ok(user_reactions)
+    }
 }
@@ -0,0 +1,257 @@
+use chrono::Utc;
+use futures::TryStreamExt;
+use mongodb::{
+    Collection, Database, IndexModel,
+    bson::{Document, doc},
+    options::ReturnDocument,
+};
+
+use async_trait::async_trait;
+
+use crate::domain::{
+    models::{
+        article::ArticleId,
+        comment::{Comment, CommentId, CommentStatus},
+        comment_service::CommentServiceError,
+        user_name::UserName,
+    },
+    repositorys::comment_repository::CommentRepository,
+};
+
+#[derive(Clone, Debug)]
+pub struct MongodbCommentRepository {
+    collection: Collection<Document>,
+}
+
+impl MongodbCommentRepository {
+    pub fn new(database: Database) -> Self {
+        let collection: Collection<Document> = database.collection("comments");
+        Self { collection }
+    }
+
+    /// コメントのコレクションに必要なインデックスを作成する
+    ///
+    /// # Errors
+    /// インデックスの作成に失敗した場合は`Err`を返す
+    pub async fn create_indexes(&self) -> Result<(), mongodb::error::Error> {
+        let index = IndexModel::builder()
+            .keys(doc! {"article_id": 1, "_id": 1})
+            .build();
+        self.collection.create_index(index).await?;
+
+        // モデレーションの一覧に使用する
+        let pending_index = IndexModel::builder()
+            .keys(doc! {"article_author.inner": 1, "status": 1, "_id": 1})
+            .build();
+        self.collection.create_index(pending_index).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CommentRepository for MongodbCommentRepository {
+    async fn get_comments_by_article(
+        &self,
+        article_id: ArticleId,
+    ) -> Result<Vec<Comment>, CommentServiceError> {
+        // `created_at`は文字列として保存されるため、作成順に並ぶ`_id`で並べ替える
+        let mut cursor = self
+            .collection
+            .find(doc! {"article_id": bson::to_bson(&article_id).unwrap()})
+            .sort(doc! {"_id": 1})
+            .await?;
+
+        let mut comments: Vec<Comment> = Vec::new();
+        while let Some(doc) = cursor.try_next().await? {
+            if let Ok(comment) = bson::from_document::<Comment>(doc) {
+                comments.push(comment);
+            }
+        }
+        Ok(comments)
+    }
+
+    async fn get_comment_by_id(&self, id: CommentId) -> Result<Comment, CommentServiceError> {
+        let filter = doc! { "_id": bson::to_bson(&id).unwrap() };
+        if let Some(doc) = self.collection.find_one(filter).await?
+            && let Ok(comment) = bson::from_document::<Comment>(doc)
+        {
+            return Ok(comment);
+        }
+        Err(CommentServiceError::CommentNotFound)
+    }
+
+    async fn add_comment(
+        &self,
+        article_id: ArticleId,
+        article_author: UserName,
+        parent_id: Option<CommentId>,
+        author: UserName,
+        content: String,
+        status: CommentStatus,
+    ) -> Result<Comment, CommentServiceError> {
+        let comment = Comment::new_comment(
+            article_id,
+            article_author,
+            parent_id,
+            author,
+            content,
+            status,
+        );
+        let comment_doc = bson::to_document(&comment).unwrap();
+        self.collection.insert_one(comment_doc).await?;
+        Ok(comment)
+    }
+
+    async fn update_comment(
+        &self,
+        id: CommentId,
+        content: String,
+    ) -> Result<Comment, CommentServiceError> {
+        let filter = doc! { "_id": bson::to_bson(&id).unwrap() };
+        let update = doc! {"$set": {
+            "content": content,
+            "updated_at": bson::to_bson(&Utc::now()).unwrap(),
+        }};
+        let doc = self
+            .collection
+            .find_one_and_update(filter, update)
+            .return_document(ReturnDocument::After)
+            .await?
+            .ok_or(CommentServiceError::CommentNotFound)?;
+        bson::from_document::<Comment>(doc).map_err(|_| CommentServiceError::CommentNotFound)
+    }
+
+    async fn delete_comment(&self, id: CommentId) -> Result<(), CommentServiceError> {
+        let filter = doc! { "_id": bson::to_bson(&id).unwrap() };
+        let update = doc! {"$set": {
+            "deleted": true,
+            "content": "",
+            "updated_at": bson::to_bson(&Utc::now()).unwrap(),
+        }};
+        let result = self.collection.update_one(filter, update).await?;
+        if result.matched_count == 1 {
+            Ok(())
+        } else {
+            Err(CommentServiceError::CommentNotFound)
+        }
+    }
+
+    async fn get_pending_comments(
+        &self,
+        article_author: &UserName,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Vec<Comment>, CommentServiceError> {
+        let filter = doc! {
+            "article_author.inner": article_author.as_str(),
+            "status": bson::to_bson(&CommentStatus::Pending).unwrap(),
+            "deleted": false,
+        };
+        let mut cursor = self
+            .collection
+            .find(filter)
+            .sort(doc! {"_id": 1})
+            .skip(skip as u64)
+            .limit(limit as i64)
+            .await?;
+
+        let mut comments: Vec<Comment> = Vec::new();
+        while let Some(doc) = cursor.try_next().await? {
+            if let Ok(comment) = bson::from_document::<Comment>(doc) {
+                comments.push(comment);
+            }
+        }
+        Ok(comments)
+    }
+
+    async fn update_comment_status(
+        &self,
+        id: CommentId,
+        status: CommentStatus,
+    ) -> Result<Comment, CommentServiceError> {
+        let filter = doc! { "_id": bson::to_bson(&id).unwrap() };
+        let update = doc! {"$set": {
+            "status": bson::to_bson(&status).unwrap(),
+            "updated_at": bson::to_bson(&Utc::now()).unwrap(),
+        }};
+        let doc = self
+            .collection
+            .find_one_and_update(filter, update)
+            .return_document(ReturnDocument::After)
+            .await?
+            .ok_or(CommentServiceError::CommentNotFound)?;
+        bson::from_document::<Comment>(doc).map_err(|_| CommentServiceError::CommentNotFound)
+    }
+
+    async fn delete_comments_by_article(
+        &self,
+        article_id: ArticleId,
+    ) -> Result<(), CommentServiceError> {
+        self.collection
+            .delete_many(doc! {"article_id": bson::to_bson(&article_id).unwrap()})
+            .await?;
+        Ok(())
+    }
+
+    async fn reassign_article_author(
+        &self,
+        from: &UserName,
+        to: &UserName,
+    ) -> Result<(), CommentServiceError> {
+        self.collection
+            .update_many(
+                doc! {"article_author.inner": from.as_str()},
+                doc! {"$set": {"article_author": bson::to_bson(to).unwrap()}},
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn reassign_comment_author(
+        &self,
+        from: &UserName,
+        to: &UserName,
+    ) -> Result<(), CommentServiceError> {
+        self.collection
+            .update_many(
+                doc! {"author.inner": from.as_str()},
+                doc! {"$set": {"author": bson::to_bson(to).unwrap()}},
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_comments_by_author(
+        &self,
+        author: &UserName,
+    ) -> Result<(), CommentServiceError> {
+        let update = doc! {"$set": {
+            "deleted": true,
+            "content": "",
+            "updated_at": bson::to_bson(&Utc::now()).unwrap(),
+        }};
+        self.collection
+            .update_many(
+                doc! {"author.inner": author.as_str(), "deleted": {"$ne": true}},
+                update,
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn count_comments_by_articles(
+        &self,
+        article_ids: &[ArticleId],
+    ) -> Result<u64, CommentServiceError> {
+        // モデレーションが導入される前のコメントは`status`を持たず、承認済みとして扱う
+        let filter = doc! {
+            "article_id": {"$in": bson::to_bson(article_ids).unwrap()},
+            "status": {"$nin": [
+                bson::to_bson(&CommentStatus::Pending).unwrap(),
+                bson::to_bson(&CommentStatus::Rejected).unwrap(),
+            ]},
+            "deleted": {"$ne": true},
+        };
+        Ok(self.collection.count_documents(filter).await?)
+    }
+}
@@ -0,0 +1,69 @@
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use mongodb::{
+    Collection,
+    bson::{Document, doc},
+};
+
+use crate::domain::models::page_cursor::PageCursor;
+
+/// 作成日時をBSONの日付型で保持するフィールド
+/// `created_at`は小数点以下の末尾の0を省いた文字列として保存され、文字列の順序が日時の順序と一致しないため、
+/// 作成日時での並べ替えやカーソルとの比較にはこちらを使用する
+pub const CREATED_AT_DATE: &str = "created_at_date";
+
+/// 日時をBSONの日付型に変換する　BSONの日付型はミリ秒までの精度のため、それより細かい部分は切り捨てる
+pub fn to_bson_date(date: DateTime<Utc>) -> bson::DateTime {
+    bson::DateTime::from_millis(date.timestamp_millis())
+}
+
+/// 作成日時とIDの組が`cursor`より前にあるドキュメントに絞り込むフィルタ
+pub fn before_cursor_filter(cursor: &PageCursor) -> Document {
+    cursor_filter(cursor, "$lt")
+}
+
+/// 作成日時とIDの組が`cursor`より後にあるドキュメントに絞り込むフィルタ
+pub fn after_cursor_filter(cursor: &PageCursor) -> Document {
+    cursor_filter(cursor, "$gt")
+}
+
+/// 並べ替えと同じく、ミリ秒に切り捨てた作成日時とIDの組で比較する
+fn cursor_filter(cursor: &PageCursor, op: &str) -> Document {
+    let created_at = to_bson_date(cursor.created_at);
+    doc! {"$or": [
+        {CREATED_AT_DATE: {op: created_at}},
+        {CREATED_AT_DATE: created_at, "_id": {op: cursor.id}},
+    ]}
+}
+
+/// `CREATED_AT_DATE`を持たないドキュメントに、`created_at`の文字列から作成日時を登録する
+/// 作成日時を日付型で保持する前に作成されたドキュメントを、一覧で正しい位置に並べる
+/// 登録したドキュメントの数を返す
+///
+/// # Errors
+/// データベースへのアクセスに失敗した場合は`Err`を返す
+pub async fn fill_missing_created_at_dates(
+    collection: &Collection<Document>,
+) -> Result<u64, mongodb::error::Error> {
+    let mut cursor = collection
+        .find(doc! {CREATED_AT_DATE: {"$exists": false}})
+        .projection(doc! {"created_at": 1})
+        .await?;
+    let mut count = 0;
+    while let Some(doc) = cursor.try_next().await? {
+        let (Some(id), Ok(created_at)) = (doc.get("_id"), doc.get_str("created_at")) else {
+            continue;
+        };
+        let Ok(created_at) = DateTime::parse_from_rfc3339(created_at) else {
+            continue;
+        };
+        collection
+            .update_one(
+                doc! {"_id": id.clone()},
+                doc! {"$set": {CREATED_AT_DATE: to_bson_date(created_at.to_utc())}},
+            )
+            .await?;
+        count += 1;
+    }
+    Ok(count)
+}
@@ -0,0 +1,156 @@
+use futures::TryStreamExt;
+use mongodb::{
+    Collection, Database, IndexModel,
+    bson::{Document, doc},
+    options::IndexOptions,
+};
+
+use async_trait::async_trait;
+
+use crate::domain::{
+    models::{follow::Follow, follow_service::FollowServiceError, user_name::UserName},
+    repositorys::follow_repository::FollowRepository,
+};
+
+#[derive(Clone, Debug)]
+pub struct MongodbFollowRepository {
+    collection: Collection<Document>,
+}
+
+impl MongodbFollowRepository {
+    pub fn new(database: Database) -> Self {
+        let collection: Collection<Document> = database.collection("follows");
+        Self { collection }
+    }
+
+    /// フォローのコレクションに必要なインデックスを作成する
+    /// 同じユーザーを重複してフォローしないようにする一意インデックスと、フォロワーの一覧に使用するインデックスを含む
+    ///
+    /// # Errors
+    /// インデックスの作成に失敗した場合は`Err`を返す
+    pub async fn create_indexes(&self) -> Result<(), mongodb::error::Error> {
+        let index = IndexModel::builder()
+            .keys(doc! {"follower.inner": 1, "followee.inner": 1})
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+        self.collection.create_index(index).await?;
+
+        let followee_index = IndexModel::builder()
+            .keys(doc! {"followee.inner": 1, "_id": -1})
+            .build();
+        self.collection.create_index(followee_index).await?;
+        Ok(())
+    }
+
+    /// フォローした日時の新しい順に取得する
+    /// `created_at`は文字列として保存されるため、作成順に並ぶ`_id`で並べ替える
+    async fn find_follows(
+        &self,
+        filter: Document,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Vec<Follow>, FollowServiceError> {
+        let mut cursor = self
+            .collection
+            .find(filter)
+            .sort(doc! {"_id": -1})
+            .skip(skip as u64)
+            .limit(limit as i64)
+            .await?;
+
+        let mut follows: Vec<Follow> = Vec::new();
+        while let Some(doc) = cursor.try_next().await? {
+            if let Ok(follow) = bson::from_document::<Follow>(doc) {
+                follows.push(follow);
+            }
+        }
+        Ok(follows)
+    }
+}
+
+#[async_trait]
+impl FollowRepository for MongodbFollowRepository {
+    async fn add_follow(
+        &self,
+        follower: &UserName,
+        followee: &UserName,
+    ) -> Result<(), FollowServiceError> {
+        // 既にフォローしている場合に、フォローした日時を変えないように`$setOnInsert`を使用する
+        let filter = doc! {
+            "follower.inner": follower.as_str(),
+            "followee.inner": followee.as_str(),
+        };
+        let follow = Follow::new(follower.clone(), followee.clone());
+        let update = doc! {"$setOnInsert": bson::to_document(&follow).unwrap()};
+        self.collection
+            .update_one(filter, update)
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    async fn remove_follow(
+        &self,
+        follower: &UserName,
+        followee: &UserName,
+    ) -> Result<(), FollowServiceError> {
+        let filter = doc! {
+            "follower.inner": follower.as_str(),
+            "followee.inner": followee.as_str(),
+        };
+        self.collection.delete_one(filter).await?;
+        Ok(())
+    }
+
+    async fn remove_follows_of(&self, user: &UserName) -> Result<(), FollowServiceError> {
+        let filter = doc! {
+            "$or": [
+                {"follower.inner": user.as_str()},
+                {"followee.inner": user.as_str()},
+            ],
+        };
+        self.collection.delete_many(filter).await?;
+        Ok(())
+    }
+
+    async fn get_followers(
+        &self,
+        followee: &UserName,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Vec<Follow>, FollowServiceError> {
+        self.find_follows(doc! {"followee.inner": followee.as_str()}, skip, limit)
+            .await
+    }
+
+    async fn get_following(
+        &self,
+        follower: &UserName,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Vec<Follow>, FollowServiceError> {
+        self.find_follows(doc! {"follower.inner": follower.as_str()}, skip, limit)
+            .await
+    }
+
+    async fn get_followee_names(
+        &self,
+        follower: &UserName,
+    ) -> Result<Vec<UserName>, FollowServiceError> {
+        let mut cursor = self
+            .collection
+            .find(doc! {"follower.inner": follower.as_str()})
+            .projection(doc! {"followee": 1})
+            .await?;
+
+        let mut names: Vec<UserName> = Vec::new();
+        while let Some(doc) = cursor.try_next().await? {
+            if let Some(followee) = doc.get("followee")
+                && let Ok(name) = bson::from_bson::<UserName>(followee.clone())
+            {
+                names.push(name);
+            }
+        }
+        Ok(names)
+    }
+}
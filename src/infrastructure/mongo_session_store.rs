@@ -0,0 +1,122 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum_login::tower_sessions::{
+    SessionStore,
+    cookie::time::OffsetDateTime,
+    session::{Id, Record},
+    session_store,
+};
+use mongodb::{
+    Collection, Database, IndexModel,
+    bson::{Document, doc},
+    options::IndexOptions,
+};
+
+/// セッションをMongoDBに保存する`SessionStore`
+/// Cloud Runで複数のインスタンスが立ち上がってもセッションを共有できるようにするために使用する
+#[derive(Clone, Debug)]
+pub struct MongodbSessionStore {
+    collection: Collection<Document>,
+}
+
+impl MongodbSessionStore {
+    pub fn new(database: Database) -> Self {
+        let collection: Collection<Document> = database.collection("sessions");
+        Self { collection }
+    }
+
+    /// 期限切れのセッションをMongoDB側で自動削除するためのTTLインデックスを作成する
+    pub async fn create_indexes(&self) -> Result<(), mongodb::error::Error> {
+        let index = IndexModel::builder()
+            .keys(doc! { "expiry_date": 1 })
+            .options(
+                IndexOptions::builder()
+                    .expire_after(Duration::from_secs(0))
+                    .build(),
+            )
+            .build();
+        self.collection.create_index(index).await?;
+        Ok(())
+    }
+}
+
+fn to_bson_datetime(date: OffsetDateTime) -> bson::DateTime {
+    bson::DateTime::from_millis((date.unix_timestamp_nanos() / 1_000_000) as i64)
+}
+
+fn backend_error(e: mongodb::error::Error) -> session_store::Error {
+    session_store::Error::Backend(e.to_string())
+}
+
+#[async_trait]
+impl SessionStore for MongodbSessionStore {
+    async fn create(&self, record: &mut Record) -> session_store::Result<()> {
+        // セッションIDが衝突した場合は振り直す
+        while self
+            .collection
+            .find_one(doc! { "_id": record.id.to_string() })
+            .await
+            .map_err(backend_error)?
+            .is_some()
+        {
+            record.id = Id::default();
+        }
+        self.save(record).await
+    }
+
+    async fn save(&self, record: &Record) -> session_store::Result<()> {
+        let data = serde_json::to_string(&record.data)
+            .map_err(|e| session_store::Error::Encode(e.to_string()))?;
+        let session_doc = doc! {
+            "_id": record.id.to_string(),
+            "data": data,
+            "expiry_date": to_bson_datetime(record.expiry_date),
+        };
+        self.collection
+            .replace_one(doc! { "_id": record.id.to_string() }, session_doc)
+            .upsert(true)
+            .await
+            .map_err(backend_error)?;
+        Ok(())
+    }
+
+    async fn load(&self, session_id: &Id) -> session_store::Result<Option<Record>> {
+        let filter = doc! {
+            "_id": session_id.to_string(),
+            "expiry_date": { "$gt": bson::DateTime::now() },
+        };
+        let Some(session_doc) = self
+            .collection
+            .find_one(filter)
+            .await
+            .map_err(backend_error)?
+        else {
+            return Ok(None);
+        };
+
+        let data = session_doc
+            .get_str("data")
+            .map_err(|e| session_store::Error::Decode(e.to_string()))?;
+        let expiry_date = session_doc
+            .get_datetime("expiry_date")
+            .map_err(|e| session_store::Error::Decode(e.to_string()))?;
+        Ok(Some(Record {
+            id: *session_id,
+            data: serde_json::from_str(data)
+                .map_err(|e| session_store::Error::Decode(e.to_string()))?,
+            expiry_date: OffsetDateTime::from_unix_timestamp_nanos(
+                expiry_date.timestamp_millis() as i128 * 1_000_000,
+            )
+            .map_err(|e| session_store::Error::Decode(e.to_string()))?,
+        }))
+    }
+
+    async fn delete(&self, session_id: &Id) -> session_store::Result<()> {
+        self.collection
+            .delete_one(doc! { "_id": session_id.to_string() })
+            .await
+            .map_err(backend_error)?;
+        Ok(())
+    }
+}
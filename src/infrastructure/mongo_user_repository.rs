@@ -1,38 +1,86 @@
 use async_trait::async_trait;
 use futures::TryStreamExt;
 use mongodb::{
-    bson::{doc, Document},
-    Collection, Database,
+    Collection, Database, IndexModel,
+    bson::{Document, doc},
+    options::ReturnDocument,
 };
 
 use crate::domain::{
     models::{
-        user::{User, UserId},
+        page_cursor::PageCursor,
+        password_hasher::PasswordHash,
+        user::{CommentModeration, User, UserId, UserRole},
         user_name::UserName,
         user_service::UserServiceError,
     },
     repositorys::user_repository::UserRepository,
 };
+use crate::infrastructure::{
+    mongo_cursor::{
+        CREATED_AT_DATE, after_cursor_filter, fill_missing_created_at_dates, to_bson_date,
+    },
+    mongo_version::insert_version_condition,
+};
 
 #[derive(Debug, Clone)]
 pub struct MongodbUserRepository {
-    database: Database,
     collection: Collection<Document>,
 }
 
 impl MongodbUserRepository {
     pub fn new(database: Database) -> Self {
         let collection: Collection<Document> = database.collection("users");
-        Self { database, collection }
+        Self { collection }
+    }
+
+    /// ユーザーのコレクションに必要なインデックスを作成する
+    /// 一覧を作成日時の順に取得するためのインデックスを含む
+    ///
+    /// # Errors
+    /// インデックスの作成に失敗した場合は`Err`を返す
+    pub async fn create_indexes(&self) -> Result<(), mongodb::error::Error> {
+        let created_index = IndexModel::builder()
+            .keys(doc! {CREATED_AT_DATE: 1, "_id": 1})
+            .build();
+        self.collection.create_index(created_index).await?;
+        Ok(())
+    }
+
+    /// 作成日時を日付型で保持する前に登録されたユーザーに、作成日時を登録する
+    /// 登録したユーザーの数を返す
+    ///
+    /// # Errors
+    /// データベースへのアクセスに失敗した場合は`Err`を返す
+    pub async fn fill_missing_created_at_dates(&self) -> Result<u64, mongodb::error::Error> {
+        fill_missing_created_at_dates(&self.collection).await
+    }
+
+    /// 条件付きの更新・削除が対象を見つけられなかった場合に、その理由を返す
+    /// ユーザー自体は存在する場合は、バージョンが一致しなかったものとみなす
+    async fn missing_reason(&self, id: UserId) -> UserServiceError {
+        let filter = doc! {"_id": bson::to_bson(&id).unwrap() };
+        match self.collection.find_one(filter).await {
+            Ok(Some(_)) => UserServiceError::VersionMismatch,
+            Ok(None) => UserServiceError::UserNotFound,
+            Err(e) => UserServiceError::DatabaseError(e),
+        }
     }
 }
 
 #[async_trait]
 impl UserRepository for MongodbUserRepository {
-    async fn get_users(&self, skip: usize, limit: usize) -> Result<Vec<User>, UserServiceError> {
+    async fn get_users(
+        &self,
+        skip: usize,
+        limit: usize,
+        cursor: Option<PageCursor>,
+    ) -> Result<Vec<User>, UserServiceError> {
+        let filter = cursor.map_or_else(Document::new, |cursor| after_cursor_filter(&cursor));
         let mut cursor = self
             .collection
-            .find(doc! {})
+            .find(filter)
+            .sort(doc! {CREATED_AT_DATE: 1, "_id": 1})
             .skip(skip as u64)
             .limit(limit as i64)
             .await
@@ -51,6 +99,13 @@ impl UserRepository for MongodbUserRepository {
         Ok(users)
     }
 
+    async fn count_users(&self) -> Result<u64, UserServiceError> {
+        self.collection
+            .count_documents(doc! {})
+            .await
+            .map_err(UserServiceError::DatabaseError)
+    }
+
     async fn get_user_by_id(&self, id: UserId) -> Result<User, UserServiceError> {
         let filter = doc! {"_id": bson::to_bson(&id).unwrap() };
         if let Some(doc) = self
@@ -58,10 +113,9 @@ impl UserRepository for MongodbUserRepository {
             .find_one(filter)
             .await
             .map_err(UserServiceError::DatabaseError)?
+            && let Ok(user) = bson::from_document::<User>(doc)
         {
-            if let Ok(user) = bson::from_document::<User>(doc) {
-                return Ok(user);
-            }
+            return Ok(user);
         }
         Err(UserServiceError::UserNotFound)
     }
@@ -73,10 +127,9 @@ impl UserRepository for MongodbUserRepository {
             .find_one(filter)
             .await
             .map_err(UserServiceError::DatabaseError)?
+            && let Ok(user) = bson::from_document::<User>(doc)
         {
-            if let Ok(user) = bson::from_document::<User>(doc) {
-                return Ok(user);
-            }
+            return Ok(user);
         }
         Err(UserServiceError::UserNotFound)
     }
@@ -88,8 +141,11 @@ impl UserRepository for MongodbUserRepository {
         intro: String,
         email: String,
         show_email: bool,
-        pw_hash: Vec<u8>,
+        pw_hash: PasswordHash,
     ) -> Result<User, UserServiceError> {
+        if UserName::is_reserved(&name) {
+            return Err(UserServiceError::ReservedUserName);
+        }
         // 重複チェック
         if self
             .collection
@@ -110,8 +166,13 @@ impl UserRepository for MongodbUserRepository {
             show_email,
             pw_hash,
             created_at: chrono::Utc::now(),
+            updated_at: None,
+            role: UserRole::default(),
+            comment_moderation: CommentModeration::default(),
+            version: 1,
         };
-        let doc = bson::to_document(&user).unwrap();
+        let mut doc = bson::to_document(&user).unwrap();
+        doc.insert(CREATED_AT_DATE, to_bson_date(user.created_at));
         self.collection
             .insert_one(doc)
             .await
@@ -127,37 +188,74 @@ impl UserRepository for MongodbUserRepository {
         intro: Option<String>,
         email: Option<String>,
         show_email: Option<bool>,
-        pw_hash: Option<Vec<u8>>,
+        pw_hash: Option<PasswordHash>,
+        comment_moderation: Option<CommentModeration>,
+        expected_version: Option<u32>,
     ) -> Result<User, UserServiceError> {
+        if name.as_deref().is_some_and(UserName::is_reserved) {
+            return Err(UserServiceError::ReservedUserName);
+        }
         // 名前を変更する場合は重複チェック
-        if let Some(ref new_name) = name {
-            if self
+        if let Some(ref new_name) = name
+            && self
                 .collection
                 .find_one(doc! {"name.inner": new_name })
                 .await
                 .map_err(UserServiceError::DatabaseError)?
                 .is_some()
-            {
-                return Err(UserServiceError::UserAlreadyExists);
-            }
+        {
+            return Err(UserServiceError::UserAlreadyExists);
         }
 
-        let mut set_doc = doc! {};
+        let mut set_doc = doc! {"updated_at": bson::to_bson(&chrono::Utc::now()).unwrap()};
         if let Some(new_name) = name {
             set_doc.insert("name", bson::to_bson(&UserName::new(new_name)).unwrap());
         }
-        if let Some(v) = display_name { set_doc.insert("display_name", v); }
-        if let Some(v) = intro { set_doc.insert("intro", v); }
-        if let Some(v) = email { set_doc.insert("email", v); }
-        if let Some(v) = show_email { set_doc.insert("show_email", v); }
-        if let Some(v) = pw_hash { set_doc.insert("pw_hash", bson::to_bson(&v).unwrap()); }
-
-        let mut update_doc = doc! {};
-        if !set_doc.is_empty() {
-            update_doc.insert("$set", set_doc);
+        if let Some(v) = display_name {
+            set_doc.insert("display_name", v);
+        }
+        if let Some(v) = intro {
+            set_doc.insert("intro", v);
+        }
+        if let Some(v) = email {
+            set_doc.insert("email", v);
+        }
+        if let Some(v) = show_email {
+            set_doc.insert("show_email", v);
+        }
+        if let Some(v) = pw_hash {
+            set_doc.insert("pw_hash", bson::to_bson(&v).unwrap());
         }
+        if let Some(v) = comment_moderation {
+            set_doc.insert("comment_moderation", bson::to_bson(&v).unwrap());
+        }
+
+        let update_doc = doc! {"$set": set_doc, "$inc": {"version": 1}};
 
+        // バージョンの確認と更新を不可分に行う
+        let mut filter = doc! {"_id": bson::to_bson(&id).unwrap() };
+        insert_version_condition(&mut filter, expected_version);
+        let Some(doc) = self
+            .collection
+            .find_one_and_update(filter, update_doc)
+            .return_document(ReturnDocument::After)
+            .await
+            .map_err(UserServiceError::DatabaseError)?
+        else {
+            return Err(self.missing_reason(id).await);
+        };
+        bson::from_document::<User>(doc).map_err(|_| UserServiceError::UserNotFound)
+    }
+
+    async fn update_role(&self, id: UserId, role: UserRole) -> Result<User, UserServiceError> {
         let filter = doc! {"_id": bson::to_bson(&id).unwrap() };
+        let update_doc = doc! {
+            "$set": {
+                "role": bson::to_bson(&role).unwrap(),
+                "updated_at": bson::to_bson(&chrono::Utc::now()).unwrap(),
+            },
+            "$inc": {"version": 1},
+        };
         self.collection
             .update_one(filter.clone(), update_doc)
             .await
@@ -168,25 +266,36 @@ impl UserRepository for MongodbUserRepository {
             .find_one(filter)
             .await
             .map_err(UserServiceError::DatabaseError)?
+            && let Ok(user) = bson::from_document::<User>(doc)
         {
-            if let Ok(user) = bson::from_document::<User>(doc) {
-                return Ok(user);
-            }
+            return Ok(user);
         }
         Err(UserServiceError::UserNotFound)
     }
 
-    async fn delete_user(&self, id: UserId) -> Result<(), UserServiceError> {
-        let filter = doc! {"_id": bson::to_bson(&id).unwrap() };
+    async fn delete_user(
+        &self,
+        id: UserId,
+        expected_version: Option<u32>,
+    ) -> Result<(), UserServiceError> {
+        let mut filter = doc! {"_id": bson::to_bson(&id).unwrap() };
+        insert_version_condition(&mut filter, expected_version);
         let result = self
             .collection
             .delete_one(filter)
             .await
             .map_err(UserServiceError::DatabaseError)?;
-        if result.deleted_count == 1 { Ok(()) } else { Err(UserServiceError::UserNotFound) }
+        if result.deleted_count == 1 {
+            Ok(())
+        } else {
+            Err(self.missing_reason(id).await)
+        }
     }
 
     async fn validate_user_name(&self, name: &str) -> Result<UserName, UserServiceError> {
+        if UserName::is_reserved(name) {
+            return Err(UserServiceError::ReservedUserName);
+        }
         if self
             .collection
             .find_one(doc! {"name.inner": name })
@@ -0,0 +1,16 @@
+use mongodb::bson::{Bson, Document, doc};
+
+/// 楽観的排他制御のため、`filter`にバージョンの条件を追加する
+/// `expected`が`None`の場合は条件を追加しない
+/// バージョンが導入される前のドキュメントはフィールドを持たないため、`0`の場合はフィールドがないものも一致させる
+pub fn insert_version_condition(filter: &mut Document, expected: Option<u32>) {
+    match expected {
+        Some(0) => {
+            filter.insert("version", doc! {"$in": [0, Bson::Null]});
+        }
+        Some(version) => {
+            filter.insert("version", version);
+        }
+        None => {}
+    }
+}
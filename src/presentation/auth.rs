@@ -0,0 +1,54 @@
+use async_trait::async_trait;
+use axum_login::{AuthnBackend, UserId};
+use serde::Deserialize;
+
+use crate::domain::models::{
+    user::User,
+    user_service::{UserService, UserServiceError},
+};
+
+/// ログイン時に送られてくる認証情報
+#[derive(Deserialize, Clone)]
+pub struct Credentials {
+    pub name: String,
+    pub password: String,
+}
+
+/// `UserService`を使ってユーザーを認証する`axum_login`のバックエンド
+#[derive(Clone)]
+pub struct AuthBackend<U: UserService> {
+    user_service: U,
+}
+
+impl<U: UserService> AuthBackend<U> {
+    pub fn new(user_service: U) -> Self {
+        AuthBackend { user_service }
+    }
+}
+
+#[async_trait]
+impl<U: UserService + Clone + Send + Sync> AuthnBackend for AuthBackend<U> {
+    type User = User;
+    type Credentials = Credentials;
+    type Error = UserServiceError;
+
+    async fn authenticate(
+        &self,
+        creds: Self::Credentials,
+    ) -> Result<Option<Self::User>, Self::Error> {
+        self.user_service
+            .authenticate(&creds.name, &creds.password)
+            .await
+    }
+
+    async fn get_user(&self, user_id: &UserId<Self>) -> Result<Option<Self::User>, Self::Error> {
+        match self.user_service.get_user_by_id(*user_id).await {
+            Ok(user) => Ok(Some(user)),
+            Err(UserServiceError::UserNotFound) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// ハンドラで使用する認証セッションの型
+pub type AuthSession<U> = axum_login::AuthSession<AuthBackend<U>>;
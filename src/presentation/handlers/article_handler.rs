@@ -1,19 +1,31 @@
 use axum::{
     Json,
-    extract::{Path, Query, State},
-    http::StatusCode,
-    response::IntoResponse,
+    extract::{Path, Query, RawQuery, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Redirect, Response},
 };
-use serde::Deserialize;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     domain::models::{
-        article::ArticleId,
-        article_query::ArticleQuery,
+        article::{Article, ArticleId, Visibility},
+        article_query::{ArticleQuery, ArticleSort},
         article_service::{ArticleService, ArticleServiceError},
+        comment_service::CommentService,
+        follow_service::FollowService,
+        page_cursor::PageCursor,
+        reaction::ReactionKind,
+        search::SearchResults,
+        tag::{normalize_tag, normalize_tags},
+        title_regex::TitleRegex,
+        user_name::UserName,
         user_service::{UserService, UserServiceError},
     },
-    presentation::handlers::{create_handler::AppState, util::*},
+    presentation::{
+        auth::AuthSession,
+        handlers::{conditional::*, create_handler::AppState, util::*},
+    },
 };
 
 #[derive(Deserialize, Debug, Clone)]
@@ -22,94 +34,630 @@ pub struct GetArticlesParams {
     skip: usize,
     #[serde(default = "default_limit")]
     limit: usize,
+    /// 前のページの`X-Next-Cursor`ヘッダの値　記事の一覧でのみ使用でき、他の一覧では指定すると`400 Bad Request`になる
+    cursor: Option<String>,
+    /// 下書きの一覧では使用しない
+    #[serde(default)]
+    sort: ArticleSort,
+    /// `true`の場合は一覧を総数などとともにエンベロープ（`Page`）に包んで返す　下書きの一覧では使用しない
+    #[serde(default)]
+    envelope: bool,
+    /// `html`の場合は本文をHTMLに変換した`content_html`を加える　記事の一覧でのみ使用する
+    #[serde(default)]
+    format: ContentFormat,
+}
+
+/// レスポンスに含める本文の形式
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentFormat {
+    /// Markdownの本文（`content`）のみを返す
+    #[default]
+    Markdown,
+    /// Markdownの本文に加えて、サニタイズされたHTMLに変換した本文（`content_html`）を返す
+    Html,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct GetArticleParams {
+    #[serde(default)]
+    format: ContentFormat,
+}
+
+/// 記事に、閲覧しているユーザーが付けたリアクションを加えたレスポンス
+#[derive(Serialize, Debug, Clone)]
+pub struct ArticleResponse {
+    #[serde(flatten)]
+    pub article: Article,
+    /// 閲覧しているユーザーが付けたリアクションの種類　未ログインの場合は空になる
+    pub my_reactions: Vec<ReactionKind>,
+    /// HTMLに変換した本文　`format=html`を指定した場合のみ含める
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_html: Option<String>,
+}
+
+/// 各記事に`viewer`が付けたリアクションを加える
+pub async fn with_my_reactions<A: ArticleService>(
+    article_service: &A,
+    articles: Vec<Article>,
+    viewer: Option<&UserName>,
+) -> Result<Vec<ArticleResponse>, ArticleServiceError> {
+    let mut my_reactions = match viewer {
+        Some(viewer) => {
+            let ids: Vec<ArticleId> = articles.iter().map(|article| article.id).collect();
+            article_service.get_user_reactions(&ids, viewer).await?
+        }
+        None => Default::default(),
+    };
+    Ok(articles
+        .into_iter()
+        .map(|article| ArticleResponse {
+            my_reactions: my_reactions.remove(&article.id).unwrap_or_default(),
+            content_html: None,
+            article,
+        })
+        .collect())
+}
+
+/// `format`が`html`の場合は、各記事の本文をHTMLに変換して加える
+pub fn with_content_html<A: ArticleService>(
+    article_service: &A,
+    mut articles: Vec<ArticleResponse>,
+    format: ContentFormat,
+) -> Vec<ArticleResponse> {
+    if format == ContentFormat::Html {
+        for response in &mut articles {
+            response.content_html = Some(
+                article_service
+                    .render_content_html(&response.article)
+                    .to_string(),
+            );
+        }
+    }
+    articles
 }
 
-pub async fn get_articles<T: ArticleService, U: UserService>(
-    State(state): State<AppState<T, U>>,
+/// 一覧には弱いETagを付け、`If-None-Match`が一致する場合は`304`を返す
+pub async fn get_articles<T, U, C, F>(
+    State(state): State<AppState<T, U, C, F>>,
+    auth_session: AuthSession<U>,
+    headers: HeaderMap,
     Query(params): Query<GetArticlesParams>,
-) -> impl IntoResponse {
+) -> impl IntoResponse
+where
+    T: ArticleService,
+    U: UserService + Clone + Send + Sync + 'static,
+    C: CommentService,
+    F: FollowService,
+{
+    let cursor = match parse_cursor(params.cursor.as_deref()) {
+        Ok(cursor) => cursor,
+        Err(status) => return (status, "Invalid cursor").into_response(),
+    };
+    // 作成日時以外の並び順では、カーソルの位置を決められない
+    if cursor.is_some() && !params.sort.supports_cursor() {
+        return (
+            StatusCode::BAD_REQUEST,
+            "cursor can only be used with sort=newest or sort=oldest",
+        )
+            .into_response();
+    }
+    let viewer = auth_session.user.map(|user| user.name);
+    let total = if params.envelope {
+        match state.article_service.count_articles(viewer.as_ref()).await {
+            Ok(total) => Some(total),
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    } else {
+        None
+    };
     match state
         .article_service
-        .get_articles(params.skip, params.limit)
+        .get_articles(
+            params.skip,
+            params.limit,
+            params.sort,
+            cursor,
+            viewer.as_ref(),
+        )
         .await
     {
-        Ok(articles) => (StatusCode::OK, Json(articles)).into_response(),
+        Ok(articles) => {
+            let next_cursor = params
+                .sort
+                .supports_cursor()
+                .then(|| {
+                    next_cursor(&articles, params.limit, |article| {
+                        PageCursor::new(article.created_at, article.id)
+                    })
+                })
+                .flatten();
+            match with_my_reactions(&state.article_service, articles, viewer.as_ref()).await {
+                Ok(articles) => {
+                    let articles =
+                        with_content_html(&state.article_service, articles, params.format);
+                    list_response(
+                        &headers,
+                        articles,
+                        total,
+                        &params,
+                        next_cursor,
+                        viewer.is_some(),
+                    )
+                }
+                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+            }
+        }
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     }
 }
 
+/// 指定したユーザーが著者の記事を取得する
+/// クエリパラメータとレスポンスは`get_articles`と同じ　ユーザーが存在しない場合は`404`を返す
+pub async fn get_user_articles<A, U, C, F>(
+    State(state): State<AppState<A, U, C, F>>,
+    auth_session: AuthSession<U>,
+    headers: HeaderMap,
+    Path(user_name): Path<String>,
+    Query(params): Query<GetArticlesParams>,
+) -> impl IntoResponse
+where
+    A: ArticleService,
+    U: UserService + Clone + Send + Sync + 'static,
+    C: CommentService,
+    F: FollowService,
+{
+    let cursor = match parse_cursor(params.cursor.as_deref()) {
+        Ok(cursor) => cursor,
+        Err(status) => return (status, "Invalid cursor").into_response(),
+    };
+    if cursor.is_some() && !params.sort.supports_cursor() {
+        return (
+            StatusCode::BAD_REQUEST,
+            "cursor can only be used with sort=newest or sort=oldest",
+        )
+            .into_response();
+    }
+    let author = match state.user_service.get_user_by_name(&user_name).await {
+        Ok(author) => author.name,
+        Err(UserServiceError::UserNotFound) => {
+            return (StatusCode::NOT_FOUND, "User not found").into_response();
+        }
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let viewer = auth_session.user.map(|user| user.name);
+    let total = if params.envelope {
+        match state
+            .article_service
+            .count_articles_by_author(author.as_str(), viewer.as_ref())
+            .await
+        {
+            Ok(total) => Some(total),
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    } else {
+        None
+    };
+    match state
+        .article_service
+        .get_articles_by_author(
+            author.as_str(),
+            params.skip,
+            params.limit,
+            params.sort,
+            cursor,
+            viewer.as_ref(),
+        )
+        .await
+    {
+        Ok(articles) => {
+            let next_cursor = params
+                .sort
+                .supports_cursor()
+                .then(|| {
+                    next_cursor(&articles, params.limit, |article| {
+                        PageCursor::new(article.created_at, article.id)
+                    })
+                })
+                .flatten();
+            match with_my_reactions(&state.article_service, articles, viewer.as_ref()).await {
+                Ok(articles) => {
+                    let articles =
+                        with_content_html(&state.article_service, articles, params.format);
+                    list_response(
+                        &headers,
+                        articles,
+                        total,
+                        &params,
+                        next_cursor,
+                        viewer.is_some(),
+                    )
+                }
+                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+            }
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// 記事の一覧を弱いETag付きで返す
+/// 一覧は記事の削除によっても変わり、更新日時からは判断できないため`If-Modified-Since`は評価しない
+/// `total`を指定した場合はエンベロープに包んで返し、総数もETagに含める
+/// 次のページがある場合は、そのカーソルを`X-Next-Cursor`ヘッダで返す
+fn list_response(
+    headers: &HeaderMap,
+    articles: Vec<ArticleResponse>,
+    total: Option<u64>,
+    params: &GetArticlesParams,
+    next_cursor: Option<String>,
+    authenticated: bool,
+) -> Response {
+    let mut etags: Vec<_> = articles.iter().map(article_etag).collect();
+    if let Some(total) = total {
+        etags.push(HeaderValue::from(total));
+    }
+    let etag = weak_etag(&etags);
+    if is_not_modified(headers, &etag, None) {
+        let mut response = (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response();
+        vary_by_viewer(&mut response, authenticated);
+        return response;
+    }
+    let mut response = match total {
+        Some(total) => {
+            let page = Page::new(articles, total, params.skip, params.limit);
+            let page = if params.cursor.is_some() {
+                page.without_next()
+            } else {
+                page
+            };
+            (StatusCode::OK, [(header::ETAG, etag)], Json(page)).into_response()
+        }
+        None => (StatusCode::OK, [(header::ETAG, etag)], Json(articles)).into_response(),
+    };
+    if let Some(next_cursor) = next_cursor
+        && let Ok(value) = HeaderValue::from_str(&next_cursor)
+    {
+        response.headers_mut().insert(NEXT_CURSOR, value);
+    }
+    vary_by_viewer(&mut response, authenticated);
+    response
+}
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct CreateArticlePayload {
-    author: String,
     title: String,
     content: String,
+    /// 省略した場合は公開記事として作成する（`publish_at`を指定した場合は下書き）
+    visibility: Option<Visibility>,
+    /// 予約投稿の日時　下書きにのみ指定できる
+    publish_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    tags: Vec<String>,
+    /// 管理者が他のユーザーの代わりに投稿する場合に、著者とするユーザー名を指定する
+    /// 指定した場合は監査ログに記録される
+    on_behalf_of: Option<String>,
 }
 
 // この関数はUserAppStateに依存していることに注意してください
-pub async fn create_article<A: ArticleService, U: UserService>(
-    State(state): State<AppState<A, U>>,
+pub async fn create_article<A, U, C, F>(
+    State(state): State<AppState<A, U, C, F>>,
+    auth_session: AuthSession<U>,
     Json(payload): Json<CreateArticlePayload>,
-) -> impl IntoResponse {
-    let author_name = match state.user_service.get_user_by_name(&payload.author).await {
-        Ok(user) => user.name,
-        Err(UserServiceError::UserNotFound) => {
+) -> impl IntoResponse
+where
+    A: ArticleService,
+    U: UserService + Clone + Send + Sync + 'static,
+    C: CommentService,
+    F: FollowService,
+{
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+
+    // 著者は原則としてログイン中のユーザーとし、管理者のみ別のユーザーを指定できる
+    let author_name = match payload.on_behalf_of {
+        None => user.name.clone(),
+        Some(_) if !user.is_admin() => {
             return (
-                StatusCode::BAD_REQUEST,
-                format!("User '{}' not found", payload.author),
+                StatusCode::FORBIDDEN,
+                "Only admins can post on behalf of another user",
             )
                 .into_response();
         }
-        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+        Some(author) => match state.user_service.get_user_by_name(&author).await {
+            Ok(author) => author.name,
+            Err(UserServiceError::UserNotFound) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    format!("User '{author}' not found"),
+                )
+                    .into_response();
+            }
+            Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+        },
     };
 
+    let visibility = payload
+        .visibility
+        .unwrap_or(if payload.publish_at.is_some() {
+            Visibility::Draft
+        } else {
+            Visibility::Published
+        });
+
     match state
         .article_service
-        .create_article(payload.title, author_name, payload.content)
+        .create_article(
+            payload.title,
+            author_name,
+            payload.content,
+            visibility,
+            payload.publish_at,
+            payload.tags,
+        )
         .await
     {
-        Ok(article) => (StatusCode::CREATED, Json(article)).into_response(),
+        Ok(article) => {
+            // 作成中に著者が削除された場合は、削除時の記事の処理から漏れた記事を残さないように取り消す
+            if let Err(UserServiceError::UserNotFound) = state
+                .user_service
+                .get_user_by_name(article.author.as_str())
+                .await
+            {
+                if let Err(e) = state.article_service.delete_article(article.id, None).await {
+                    tracing::warn!(
+                        "Failed to delete article {} of deleted user: {e}",
+                        article.id
+                    );
+                }
+                return (
+                    StatusCode::BAD_REQUEST,
+                    format!("User '{}' not found", article.author),
+                )
+                    .into_response();
+            }
+            if article.author != user.name {
+                tracing::info!(
+                    target: "audit",
+                    admin = %user.name,
+                    author = %article.author,
+                    article_id = %article.id,
+                    "article created on behalf of another user"
+                );
+            }
+            let etag = strong_etag(article.id, article.last_modified(), article.version);
+            (StatusCode::CREATED, [(header::ETAG, etag)], Json(article)).into_response()
+        }
+        Err(e @ ArticleServiceError::InvalidSchedule) => {
+            (StatusCode::BAD_REQUEST, e.to_string()).into_response()
+        }
+        Err(e @ ArticleServiceError::ArticleAlreadyExists) => {
+            (StatusCode::CONFLICT, e.to_string()).into_response()
+        }
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     }
 }
 
-pub async fn get_article_by_id<A: ArticleService, U: UserService>(
-    State(state): State<AppState<A, U>>,
+/// 強いETagと`Last-Modified`を付け、`If-None-Match`か`If-Modified-Since`の条件を満たす場合は`304`を返す
+pub async fn get_article_by_id<A, U, C, F>(
+    State(state): State<AppState<A, U, C, F>>,
+    auth_session: AuthSession<U>,
+    headers: HeaderMap,
     Path(id): Path<String>,
-) -> impl IntoResponse {
+    Query(params): Query<GetArticleParams>,
+) -> impl IntoResponse
+where
+    A: ArticleService,
+    U: UserService + Clone + Send + Sync + 'static,
+    C: CommentService,
+    F: FollowService,
+{
     let Ok(oid) = ArticleId::parse_str(&id) else {
         return (StatusCode::BAD_REQUEST, "Invalid ID format").into_response();
     };
 
+    let viewer = auth_session.user.map(|user| user.name);
     match state.article_service.get_article_by_id(oid).await {
-        Ok(article) => (StatusCode::OK, Json(article)).into_response(),
-        Err(ArticleServiceError::ArticleNotFound) => {
+        Ok(article) if article.is_viewable_by(viewer.as_ref()) => {
+            article_response(
+                &state.article_service,
+                &headers,
+                article,
+                viewer.as_ref(),
+                params.format,
+            )
+            .await
+        }
+        // 閲覧できない記事は存在自体を明かさないように404を返す
+        Ok(_) | Err(ArticleServiceError::ArticleNotFound) => {
+            (StatusCode::NOT_FOUND, "Article not found").into_response()
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// 著者のユーザー名とスラッグから記事を取得する
+/// 過去のスラッグを指定した場合は、現在のスラッグへ恒久的にリダイレクトする（`308`）
+/// リダイレクト先はパスの接頭辞に依存しないように、現在のスラッグのみの相対パスとする
+pub async fn get_article_by_slug<A, U, C, F>(
+    State(state): State<AppState<A, U, C, F>>,
+    auth_session: AuthSession<U>,
+    headers: HeaderMap,
+    Path((user_name, slug)): Path<(String, String)>,
+    RawQuery(query): RawQuery,
+    Query(params): Query<GetArticleParams>,
+) -> impl IntoResponse
+where
+    A: ArticleService,
+    U: UserService + Clone + Send + Sync + 'static,
+    C: CommentService,
+    F: FollowService,
+{
+    let viewer = auth_session.user.map(|user| user.name);
+    match state
+        .article_service
+        .get_article_by_slug(&user_name, &slug)
+        .await
+    {
+        Ok(article) if article.is_viewable_by(viewer.as_ref()) => {
+            if article.slug != slug {
+                let location = match query {
+                    Some(query) => format!("{}?{query}", article.slug),
+                    None => article.slug,
+                };
+                return Redirect::permanent(&location).into_response();
+            }
+            article_response(
+                &state.article_service,
+                &headers,
+                article,
+                viewer.as_ref(),
+                params.format,
+            )
+            .await
+        }
+        Ok(_) | Err(ArticleServiceError::ArticleNotFound) => {
             (StatusCode::NOT_FOUND, "Article not found").into_response()
         }
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     }
 }
 
+/// 記事のレスポンスの強いETag
+/// リアクションの変化でも表現が変わるため`last_modified`を使用し、閲覧しているユーザーのリアクションも含める
+fn article_etag(response: &ArticleResponse) -> HeaderValue {
+    let ArticleResponse {
+        article,
+        my_reactions,
+        ..
+    } = response;
+    let my_reactions: Vec<String> = my_reactions.iter().map(ToString::to_string).collect();
+    strong_etag_with_variant(
+        article.id,
+        article.last_modified(),
+        article.version,
+        &my_reactions.join("."),
+    )
+}
+
+/// 1件の記事のレスポンス
+/// 強いETagと`Last-Modified`を付け、`If-None-Match`か`If-Modified-Since`の条件を満たす場合は`304`を返す
+async fn article_response<A: ArticleService>(
+    article_service: &A,
+    headers: &HeaderMap,
+    article: Article,
+    viewer: Option<&UserName>,
+    format: ContentFormat,
+) -> Response {
+    let mut articles = match with_my_reactions(article_service, vec![article], viewer).await {
+        Ok(articles) => articles,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let etag = article_etag(&articles[0]);
+    let last_modified = articles[0].article.last_modified();
+    let cache_headers = [
+        (header::ETAG, etag.clone()),
+        (header::LAST_MODIFIED, http_date(last_modified)),
+    ];
+    let mut response = if is_not_modified(headers, &etag, Some(last_modified)) {
+        (StatusCode::NOT_MODIFIED, cache_headers).into_response()
+    } else {
+        articles = with_content_html(article_service, articles, format);
+        (StatusCode::OK, cache_headers, Json(articles.remove(0))).into_response()
+    };
+    vary_by_viewer(&mut response, viewer.is_some());
+    response
+}
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct UpdateArticlePayload {
     title: Option<String>,
     content: Option<String>,
+    visibility: Option<Visibility>,
+    /// `null`を指定した場合は予約を取り消す
+    #[serde(default, deserialize_with = "deserialize_some")]
+    publish_at: Option<Option<DateTime<Utc>>>,
+    /// 指定した場合はタグをすべて置き換える
+    tags: Option<Vec<String>>,
 }
 
-pub async fn update_article<A: ArticleService, U: UserService>(
-    State(state): State<AppState<A, U>>,
+/// `If-Match`ヘッダを指定した場合は、現在の記事のETagと比較して更新・削除の前提となるバージョンを返す
+/// 更新のレスポンスのETagと、`viewer`が取得したレスポンスのETag（リアクションを含む）のどちらとも比較する
+async fn article_if_match<A: ArticleService>(
+    article_service: &A,
+    headers: &HeaderMap,
+    id: ArticleId,
+    viewer: Option<&UserName>,
+) -> Result<Option<u32>, Response> {
+    if !headers.contains_key(header::IF_MATCH) {
+        return Ok(None);
+    }
+    let article = match article_service.get_article_by_id(id).await {
+        Ok(article) => article,
+        Err(ArticleServiceError::ArticleNotFound) => {
+            return Err((StatusCode::NOT_FOUND, "Article not found").into_response());
+        }
+        Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()),
+    };
+    let version = article.version;
+    let etag = strong_etag(article.id, article.last_modified(), version);
+    let viewer_etag = match with_my_reactions(article_service, vec![article], viewer).await {
+        Ok(articles) => article_etag(&articles[0]),
+        Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()),
+    };
+    if_match_version(headers, &[etag, viewer_etag], version).map_err(IntoResponse::into_response)
+}
+
+/// `If-Match`ヘッダを指定した場合は、記事のETagが一致するときのみ更新する
+pub async fn update_article<A, U, C, F>(
+    State(state): State<AppState<A, U, C, F>>,
+    auth_session: AuthSession<U>,
     Path(id): Path<String>,
+    headers: HeaderMap,
     Json(payload): Json<UpdateArticlePayload>,
-) -> impl IntoResponse {
+) -> impl IntoResponse
+where
+    A: ArticleService,
+    U: UserService + Clone + Send + Sync + 'static,
+    C: CommentService,
+    F: FollowService,
+{
     let Ok(oid) = ArticleId::parse_str(&id) else {
         return (StatusCode::BAD_REQUEST, "Invalid ID format").into_response();
     };
+    let viewer = auth_session.user.map(|user| user.name);
+    let expected_version =
+        match article_if_match(&state.article_service, &headers, oid, viewer.as_ref()).await {
+            Ok(version) => version,
+            Err(response) => return response,
+        };
 
     match state
         .article_service
-        .update_article(oid, payload.title, payload.content)
+        .update_article(
+            oid,
+            payload.title,
+            payload.content,
+            payload.visibility,
+            payload.publish_at,
+            payload.tags,
+            expected_version,
+        )
         .await
     {
-        Ok(article) => (StatusCode::OK, Json(article)).into_response(),
+        Ok(article) => {
+            let etag = strong_etag(article.id, article.last_modified(), article.version);
+            (StatusCode::OK, [(header::ETAG, etag)], Json(article)).into_response()
+        }
+        Err(e @ ArticleServiceError::InvalidSchedule) => {
+            (StatusCode::BAD_REQUEST, e.to_string()).into_response()
+        }
+        Err(e @ ArticleServiceError::ArticleAlreadyExists) => {
+            (StatusCode::CONFLICT, e.to_string()).into_response()
+        }
+        Err(e @ ArticleServiceError::VersionMismatch) => {
+            (StatusCode::PRECONDITION_FAILED, e.to_string()).into_response()
+        }
         Err(e) => {
             // Assuming the service might return an error for not found cases
             (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
@@ -117,45 +665,490 @@ pub async fn update_article<A: ArticleService, U: UserService>(
     }
 }
 
-pub async fn delete_article<A: ArticleService, U: UserService>(
-    State(state): State<AppState<A, U>>,
+/// `If-Match`ヘッダを指定した場合は、記事のETagが一致するときのみ削除する
+pub async fn delete_article<A, U, C, F>(
+    State(state): State<AppState<A, U, C, F>>,
+    auth_session: AuthSession<U>,
     Path(id): Path<String>,
-) -> impl IntoResponse {
+    headers: HeaderMap,
+) -> impl IntoResponse
+where
+    A: ArticleService,
+    U: UserService + Clone + Send + Sync + 'static,
+    C: CommentService,
+    F: FollowService,
+{
     let Ok(oid) = ArticleId::parse_str(&id) else {
         return (StatusCode::BAD_REQUEST, "Invalid ID format").into_response();
     };
+    let viewer = auth_session.user.map(|user| user.name);
+    let expected_version =
+        match article_if_match(&state.article_service, &headers, oid, viewer.as_ref()).await {
+            Ok(version) => version,
+            Err(response) => return response,
+        };
 
-    match state.article_service.delete_article(oid).await {
-        Ok(_) => (StatusCode::NO_CONTENT).into_response(),
+    match state
+        .article_service
+        .delete_article(oid, expected_version)
+        .await
+    {
+        Ok(_) => {
+            // 記事は削除済みのため、コメントの削除に失敗しても成功として扱う
+            if let Err(e) = state.comment_service.delete_comments_by_article(oid).await {
+                tracing::warn!("Failed to delete comments of article {oid}: {e}");
+            }
+            (StatusCode::NO_CONTENT).into_response()
+        }
+        Err(e @ ArticleServiceError::VersionMismatch) => {
+            (StatusCode::PRECONDITION_FAILED, e.to_string()).into_response()
+        }
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     }
 }
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct SearchParams {
+    /// 全文検索のクエリ　指定した場合は、他の条件で絞り込んだ記事を関連度の高い順に返す
+    q: Option<String>,
+    /// タイトルに含まれる文字列　大文字・小文字を区別しない
     title_q: Option<String>,
+    /// タイトルが一致する正規表現　長さや構文が制限される（`TitleRegex::new`）
+    title_regex: Option<String>,
     author: Option<String>,
+    /// カンマ区切りのタグ　すべてのタグが付いた記事に絞り込む
+    tags: Option<String>,
+    /// カンマ区切りのタグ　いずれかのタグが付いた記事に絞り込む
+    any_tags: Option<String>,
     #[serde(default = "default_skip")]
     skip: usize,
     #[serde(default = "default_limit")]
     limit: usize,
+    /// 検索結果はカーソルに対応していないため、指定した場合は`400 Bad Request`を返す
+    cursor: Option<String>,
+    #[serde(default)]
+    sort: ArticleSort,
+    /// `true`の場合は一覧を総数などとともにエンベロープ（`Page`）に包んで返す
+    #[serde(default)]
+    envelope: bool,
 }
 
-pub async fn search_articles<A: ArticleService, U: UserService>(
-    State(state): State<AppState<A, U>>,
+pub async fn search_articles<A, U, C, F>(
+    State(state): State<AppState<A, U, C, F>>,
+    auth_session: AuthSession<U>,
     Query(params): Query<SearchParams>,
-) -> impl IntoResponse {
+) -> impl IntoResponse
+where
+    A: ArticleService,
+    U: UserService + Clone + Send + Sync + 'static,
+    C: CommentService,
+    F: FollowService,
+{
+    if params.cursor.is_some() {
+        return unsupported_cursor();
+    }
+    let (Some(tags), Some(any_tags)) = (split_tags(params.tags), split_tags(params.any_tags))
+    else {
+        return (StatusCode::BAD_REQUEST, "Tag must not be empty").into_response();
+    };
+    // 正規表現はデータベースに渡す前に検証する
+    let title_regex = match params
+        .title_regex
+        .as_deref()
+        .map(TitleRegex::new)
+        .transpose()
+    {
+        Ok(title_regex) => title_regex,
+        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    };
     let query = ArticleQuery {
         title: params.title_q,
+        title_regex,
         author: params.author,
+        tags,
+        any_tags,
+        sort: params.sort,
+    };
+
+    let viewer = auth_session.user.map(|user| user.name);
+    if let Some(q) = params.q {
+        return match state
+            .article_service
+            .search_full_text(&q, query, params.skip, params.limit, viewer.as_ref())
+            .await
+        {
+            Ok(SearchResults { hits, total }) if params.envelope => (
+                StatusCode::OK,
+                Json(Page::new(hits, total, params.skip, params.limit)),
+            )
+                .into_response(),
+            Ok(SearchResults { hits, .. }) => (StatusCode::OK, Json(hits)).into_response(),
+            Err(e @ ArticleServiceError::SearchTimeout) => {
+                (StatusCode::BAD_REQUEST, e.to_string()).into_response()
+            }
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        };
+    }
+    search_response(
+        &state.article_service,
+        query,
+        params.skip,
+        params.limit,
+        params.envelope,
+        viewer.as_ref(),
+    )
+    .await
+}
+
+/// カーソルに対応していない一覧で`cursor`が指定された場合のレスポンス
+/// 無視すると常に先頭のページを返し、続きを取得しているつもりのクライアントが同じページを繰り返し取得してしまう
+fn unsupported_cursor() -> Response {
+    (
+        StatusCode::BAD_REQUEST,
+        "cursor is not supported for this list; use skip instead",
+    )
+        .into_response()
+}
+
+/// カンマ区切りのタグを分割する
+/// 指定されたタグが正規化するとすべて空になる場合は、絞り込みが外れないように`None`を返す
+fn split_tags(tags: Option<String>) -> Option<Vec<String>> {
+    let Some(tags) = tags else {
+        return Some(Vec::new());
+    };
+    let tags: Vec<String> = tags.split(',').map(String::from).collect();
+    (!normalize_tags(&tags).is_empty()).then_some(tags)
+}
+
+/// 記事を検索して返す　`envelope`が`true`の場合は総数とともにエンベロープに包む
+async fn search_response<A: ArticleService>(
+    article_service: &A,
+    query: ArticleQuery,
+    skip: usize,
+    limit: usize,
+    envelope: bool,
+    viewer: Option<&UserName>,
+) -> Response {
+    let total = if envelope {
+        match article_service
+            .count_search_articles(query.clone(), viewer)
+            .await
+        {
+            Ok(total) => Some(total),
+            Err(e @ ArticleServiceError::SearchTimeout) => {
+                return (StatusCode::BAD_REQUEST, e.to_string()).into_response();
+            }
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    } else {
+        None
     };
+    match article_service
+        .search_articles(skip, limit, query, viewer)
+        .await
+    {
+        Ok(articles) => match total {
+            Some(total) => (
+                StatusCode::OK,
+                Json(Page::new(articles, total, skip, limit)),
+            )
+                .into_response(),
+            None => (StatusCode::OK, Json(articles)).into_response(),
+        },
+        Err(e @ ArticleServiceError::SearchTimeout) => {
+            (StatusCode::BAD_REQUEST, e.to_string()).into_response()
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// 公開記事に付いているタグと、そのタグが付いた記事の数を取得する
+pub async fn get_tags<A: ArticleService, U: UserService, C: CommentService, F: FollowService>(
+    State(state): State<AppState<A, U, C, F>>,
+) -> impl IntoResponse {
+    match state.article_service.get_tag_counts().await {
+        Ok(tags) => (StatusCode::OK, Json(tags)).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// 指定したタグが付いた記事を取得する
+/// タグは正規化して比較するため、全角・半角や大文字・小文字の違いは無視される
+pub async fn get_tag_articles<A, U, C, F>(
+    State(state): State<AppState<A, U, C, F>>,
+    auth_session: AuthSession<U>,
+    Path(tag): Path<String>,
+    Query(params): Query<GetArticlesParams>,
+) -> impl IntoResponse
+where
+    A: ArticleService,
+    U: UserService + Clone + Send + Sync + 'static,
+    C: CommentService,
+    F: FollowService,
+{
+    if params.cursor.is_some() {
+        return unsupported_cursor();
+    }
+    if normalize_tag(&tag).is_none() {
+        return (StatusCode::BAD_REQUEST, "Tag must not be empty").into_response();
+    }
+    let query = ArticleQuery {
+        tags: vec![tag],
+        sort: params.sort,
+        ..Default::default()
+    };
+
+    let viewer = auth_session.user.map(|user| user.name);
+    search_response(
+        &state.article_service,
+        query,
+        params.skip,
+        params.limit,
+        params.envelope,
+        viewer.as_ref(),
+    )
+    .await
+}
+
+/// ログイン中のユーザー自身の下書きを取得する
+/// 本人以外からのアクセスは`require_self`ミドルウェアで拒否される
+pub async fn get_drafts<A, U, C, F>(
+    State(state): State<AppState<A, U, C, F>>,
+    auth_session: AuthSession<U>,
+    Query(params): Query<GetArticlesParams>,
+) -> impl IntoResponse
+where
+    A: ArticleService,
+    U: UserService + Clone + Send + Sync + 'static,
+    C: CommentService,
+    F: FollowService,
+{
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    if params.cursor.is_some() {
+        return unsupported_cursor();
+    }
 
     match state
         .article_service
-        .search_articles(params.skip, params.limit, query)
+        .get_drafts(&user.name, params.skip, params.limit)
         .await
     {
         Ok(articles) => (StatusCode::OK, Json(articles)).into_response(),
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     }
 }
+
+/// IDの記事を取得し、`viewer`が閲覧できる場合のみ返す
+/// 閲覧できない場合は記事が存在しない場合と同じレスポンスを返す
+pub async fn find_viewable_article<A: ArticleService>(
+    article_service: &A,
+    id: &str,
+    viewer: Option<&UserName>,
+) -> Result<Article, Response> {
+    let Ok(oid) = ArticleId::parse_str(id) else {
+        return Err((StatusCode::BAD_REQUEST, "Invalid ID format").into_response());
+    };
+    match article_service.get_article_by_id(oid).await {
+        Ok(article) if article.is_viewable_by(viewer) => Ok(article),
+        Ok(_) | Err(ArticleServiceError::ArticleNotFound) => {
+            Err((StatusCode::NOT_FOUND, "Article not found").into_response())
+        }
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()),
+    }
+}
+
+/// 記事の版の一覧を取得する
+/// 記事を閲覧できるユーザーのみが取得できる
+pub async fn get_revisions<A, U, C, F>(
+    State(state): State<AppState<A, U, C, F>>,
+    auth_session: AuthSession<U>,
+    Path(id): Path<String>,
+) -> impl IntoResponse
+where
+    A: ArticleService,
+    U: UserService + Clone + Send + Sync + 'static,
+    C: CommentService,
+    F: FollowService,
+{
+    let viewer = auth_session.user.map(|user| user.name);
+    let article = match find_viewable_article(&state.article_service, &id, viewer.as_ref()).await {
+        Ok(article) => article,
+        Err(response) => return response,
+    };
+
+    match state.article_service.get_revisions(article.id).await {
+        Ok(revisions) => (StatusCode::OK, Json(revisions)).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+pub async fn get_revision<A, U, C, F>(
+    State(state): State<AppState<A, U, C, F>>,
+    auth_session: AuthSession<U>,
+    Path((id, rev)): Path<(String, u32)>,
+) -> impl IntoResponse
+where
+    A: ArticleService,
+    U: UserService + Clone + Send + Sync + 'static,
+    C: CommentService,
+    F: FollowService,
+{
+    let viewer = auth_session.user.map(|user| user.name);
+    let article = match find_viewable_article(&state.article_service, &id, viewer.as_ref()).await {
+        Ok(article) => article,
+        Err(response) => return response,
+    };
+
+    match state.article_service.get_revision(article.id, rev).await {
+        Ok(revision) => (StatusCode::OK, Json(revision)).into_response(),
+        Err(ArticleServiceError::RevisionNotFound) => {
+            (StatusCode::NOT_FOUND, "Revision not found").into_response()
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct DiffParams {
+    from: u32,
+    /// 省略した場合は最新の版と比較する
+    to: Option<u32>,
+}
+
+/// 記事の2つの版の行単位の差分を取得する
+pub async fn diff_revisions<A, U, C, F>(
+    State(state): State<AppState<A, U, C, F>>,
+    auth_session: AuthSession<U>,
+    Path(id): Path<String>,
+    Query(params): Query<DiffParams>,
+) -> impl IntoResponse
+where
+    A: ArticleService,
+    U: UserService + Clone + Send + Sync + 'static,
+    C: CommentService,
+    F: FollowService,
+{
+    let viewer = auth_session.user.map(|user| user.name);
+    let article = match find_viewable_article(&state.article_service, &id, viewer.as_ref()).await {
+        Ok(article) => article,
+        Err(response) => return response,
+    };
+
+    let to = params.to.unwrap_or(article.revision);
+    match state
+        .article_service
+        .diff_revisions(article.id, params.from, to)
+        .await
+    {
+        Ok(diff) => (StatusCode::OK, Json(diff)).into_response(),
+        Err(ArticleServiceError::RevisionNotFound) => {
+            (StatusCode::NOT_FOUND, "Revision not found").into_response()
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// 記事を指定した版の内容に戻す
+/// 復元した内容は新しい版として記録される
+/// 著者以外からのアクセスは`require_article_owner`ミドルウェアで拒否される
+pub async fn restore_revision<
+    A: ArticleService,
+    U: UserService,
+    C: CommentService,
+    F: FollowService,
+>(
+    State(state): State<AppState<A, U, C, F>>,
+    Path((id, rev)): Path<(String, u32)>,
+) -> impl IntoResponse {
+    let Ok(oid) = ArticleId::parse_str(&id) else {
+        return (StatusCode::BAD_REQUEST, "Invalid ID format").into_response();
+    };
+
+    match state.article_service.restore_revision(oid, rev).await {
+        Ok(article) => {
+            let etag = strong_etag(article.id, article.last_modified(), article.version);
+            (StatusCode::OK, [(header::ETAG, etag)], Json(article)).into_response()
+        }
+        Err(ArticleServiceError::RevisionNotFound) => {
+            (StatusCode::NOT_FOUND, "Revision not found").into_response()
+        }
+        Err(e @ ArticleServiceError::ArticleAlreadyExists) => {
+            (StatusCode::CONFLICT, e.to_string()).into_response()
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// ログイン中のユーザーとして記事にリアクションを付ける
+/// 既に同じ種類のリアクションを付けている場合は何もせず、現在の記事を返す
+pub async fn add_reaction<A, U, C, F>(
+    State(state): State<AppState<A, U, C, F>>,
+    auth_session: AuthSession<U>,
+    Path((id, kind)): Path<(String, ReactionKind)>,
+) -> impl IntoResponse
+where
+    A: ArticleService,
+    U: UserService + Clone + Send + Sync + 'static,
+    C: CommentService,
+    F: FollowService,
+{
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let article = match find_viewable_article(&state.article_service, &id, Some(&user.name)).await {
+        Ok(article) => article,
+        Err(response) => return response,
+    };
+    let result = state
+        .article_service
+        .add_reaction(article.id, user.name.clone(), kind)
+        .await;
+    reaction_response(&state.article_service, result, &user.name).await
+}
+
+/// ログイン中のユーザーが付けたリアクションを取り消す
+/// 同じ種類のリアクションを付けていない場合は何もせず、現在の記事を返す
+pub async fn remove_reaction<A, U, C, F>(
+    State(state): State<AppState<A, U, C, F>>,
+    auth_session: AuthSession<U>,
+    Path((id, kind)): Path<(String, ReactionKind)>,
+) -> impl IntoResponse
+where
+    A: ArticleService,
+    U: UserService + Clone + Send + Sync + 'static,
+    C: CommentService,
+    F: FollowService,
+{
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let article = match find_viewable_article(&state.article_service, &id, Some(&user.name)).await {
+        Ok(article) => article,
+        Err(response) => return response,
+    };
+    let result = state
+        .article_service
+        .remove_reaction(article.id, &user.name, kind)
+        .await;
+    reaction_response(&state.article_service, result, &user.name).await
+}
+
+async fn reaction_response<A: ArticleService>(
+    article_service: &A,
+    result: Result<Article, ArticleServiceError>,
+    user: &UserName,
+) -> Response {
+    let article = match result {
+        Ok(article) => article,
+        Err(e @ ArticleServiceError::ArticleNotFound) => {
+            return (StatusCode::NOT_FOUND, e.to_string()).into_response();
+        }
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    match with_my_reactions(article_service, vec![article], Some(user)).await {
+        Ok(mut articles) => (StatusCode::OK, Json(articles.remove(0))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
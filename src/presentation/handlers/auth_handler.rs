@@ -0,0 +1,59 @@
+use axum::{Json, http::StatusCode};
+
+use crate::{
+    domain::models::user_service::UserService,
+    presentation::{
+        auth::{AuthSession, Credentials},
+        handlers::user_handler::UserResponse,
+    },
+};
+
+pub async fn login<U: UserService + Clone + Send + Sync + 'static>(
+    mut auth_session: AuthSession<U>,
+    Json(creds): Json<Credentials>,
+) -> Result<Json<UserResponse>, StatusCode> {
+    let user = match auth_session.authenticate(creds).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return Err(StatusCode::UNAUTHORIZED),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+    auth_session
+        .login(&user)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let user_response = UserResponse {
+        id: user.id,
+        name: user.name.to_string(),
+        display_name: user.display_name,
+        intro: user.intro,
+        email: Some(user.email),
+        comment_moderation: user.comment_moderation,
+    };
+    Ok(Json(user_response))
+}
+
+pub async fn logout<U: UserService + Clone + Send + Sync + 'static>(
+    mut auth_session: AuthSession<U>,
+) -> StatusCode {
+    match auth_session.logout().await {
+        Ok(_) => StatusCode::NO_CONTENT,
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// ログイン中のユーザー自身の情報を返す
+/// 自分自身の情報なので`show_email`に関わらずメールアドレスを含める
+pub async fn me<U: UserService + Clone + Send + Sync + 'static>(
+    auth_session: AuthSession<U>,
+) -> Result<Json<UserResponse>, StatusCode> {
+    let user = auth_session.user.ok_or(StatusCode::UNAUTHORIZED)?;
+    let user_response = UserResponse {
+        id: user.id,
+        name: user.name.to_string(),
+        display_name: user.display_name,
+        intro: user.intro,
+        email: Some(user.email),
+        comment_moderation: user.comment_moderation,
+    };
+    Ok(Json(user_response))
+}
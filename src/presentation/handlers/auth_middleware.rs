@@ -0,0 +1,123 @@
+use axum::{
+    extract::{Path, Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use serde::Deserialize;
+
+use crate::{
+    domain::models::{
+        article::ArticleId,
+        article_service::{ArticleService, ArticleServiceError},
+        comment::CommentId,
+        comment_service::{CommentService, CommentServiceError},
+        follow_service::FollowService,
+        user_service::UserService,
+    },
+    presentation::{auth::AuthSession, handlers::create_handler::AppState},
+};
+
+/// 記事に関するルートのパスパラメータ
+/// `/articles/{id}/revisions/{rev}`のように他のパラメータを含むルートでも使用できる
+#[derive(Deserialize)]
+pub struct ArticlePath {
+    id: String,
+}
+
+/// ログイン中のユーザーが記事の著者である場合のみ後続のハンドラを実行する
+/// 未ログインの場合は`401`、著者でない場合は`403`を返す
+pub async fn require_article_owner<A, U, C, F>(
+    State(state): State<AppState<A, U, C, F>>,
+    auth_session: AuthSession<U>,
+    Path(ArticlePath { id }): Path<ArticlePath>,
+    request: Request,
+    next: Next,
+) -> Response
+where
+    A: ArticleService + Clone + Send + Sync + 'static,
+    U: UserService + Clone + Send + Sync + 'static,
+    C: CommentService + Clone + Send + Sync + 'static,
+    F: FollowService + Clone + Send + Sync + 'static,
+{
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let Ok(oid) = ArticleId::parse_str(&id) else {
+        return (StatusCode::BAD_REQUEST, "Invalid ID format").into_response();
+    };
+
+    match state.article_service.get_article_by_id(oid).await {
+        Ok(article) if article.author == user.name => next.run(request).await,
+        Ok(_) => StatusCode::FORBIDDEN.into_response(),
+        Err(ArticleServiceError::ArticleNotFound) => {
+            (StatusCode::NOT_FOUND, "Article not found").into_response()
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// コメントに関するルートのパスパラメータ
+#[derive(Deserialize)]
+pub struct CommentPath {
+    id: String,
+    comment_id: String,
+}
+
+/// ログイン中のユーザーがコメントの著者である場合のみ後続のハンドラを実行する
+/// 未ログインの場合は`401`、著者でない場合は`403`、コメントがパスの記事のものでない場合は`404`を返す
+pub async fn require_comment_author<A, U, C, F>(
+    State(state): State<AppState<A, U, C, F>>,
+    auth_session: AuthSession<U>,
+    Path(CommentPath { id, comment_id }): Path<CommentPath>,
+    request: Request,
+    next: Next,
+) -> Response
+where
+    A: ArticleService + Clone + Send + Sync + 'static,
+    U: UserService + Clone + Send + Sync + 'static,
+    C: CommentService + Clone + Send + Sync + 'static,
+    F: FollowService + Clone + Send + Sync + 'static,
+{
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let (Ok(article_id), Ok(comment_id)) =
+        (ArticleId::parse_str(&id), CommentId::parse_str(&comment_id))
+    else {
+        return (StatusCode::BAD_REQUEST, "Invalid ID format").into_response();
+    };
+
+    match state.comment_service.get_comment_by_id(comment_id).await {
+        Ok(comment) if comment.article_id != article_id => {
+            (StatusCode::NOT_FOUND, "Comment not found").into_response()
+        }
+        Ok(comment) if comment.author == user.name => next.run(request).await,
+        Ok(_) => StatusCode::FORBIDDEN.into_response(),
+        Err(CommentServiceError::CommentNotFound) => {
+            (StatusCode::NOT_FOUND, "Comment not found").into_response()
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// ログイン中のユーザーがパスの`user_name`本人である場合のみ後続のハンドラを実行する
+/// 未ログインの場合は`401`、本人でない場合は`403`を返す
+pub async fn require_self<U>(
+    auth_session: AuthSession<U>,
+    Path(user_name): Path<String>,
+    request: Request,
+    next: Next,
+) -> Response
+where
+    U: UserService + Clone + Send + Sync + 'static,
+{
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    if user.name.as_str() != user_name {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    next.run(request).await
+}
@@ -0,0 +1,276 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+
+use crate::{
+    domain::models::{
+        article_service::ArticleService,
+        comment::{CommentId, CommentStatus},
+        comment_service::{CommentService, CommentServiceError},
+        follow_service::FollowService,
+        user::CommentModeration,
+        user_service::{UserService, UserServiceError},
+    },
+    presentation::{
+        auth::AuthSession,
+        handlers::{article_handler::find_viewable_article, create_handler::AppState, util::*},
+    },
+};
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CreateCommentPayload {
+    pub content: String,
+    /// 返信先のコメントのID　記事への直接のコメントの場合は省略する
+    pub parent_id: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct UpdateCommentPayload {
+    pub content: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ModerationParams {
+    #[serde(default = "default_skip")]
+    skip: usize,
+    #[serde(default = "default_limit")]
+    limit: usize,
+}
+
+fn comment_error_response(e: CommentServiceError) -> Response {
+    match e {
+        CommentServiceError::CommentNotFound => {
+            (StatusCode::NOT_FOUND, e.to_string()).into_response()
+        }
+        CommentServiceError::ParentNotFound | CommentServiceError::EmptyContent => {
+            (StatusCode::BAD_REQUEST, e.to_string()).into_response()
+        }
+        CommentServiceError::CommentsDisabled => {
+            (StatusCode::FORBIDDEN, e.to_string()).into_response()
+        }
+        CommentServiceError::DatabaseError(_) => {
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+        }
+    }
+}
+
+/// 閲覧できる記事のコメントを、返信をネストしたスレッドとして返す
+/// 承認待ちのコメントは、記事の著者とコメントの著者にのみ返す
+pub async fn get_comments<A, U, C, F>(
+    State(state): State<AppState<A, U, C, F>>,
+    auth_session: AuthSession<U>,
+    Path(id): Path<String>,
+) -> impl IntoResponse
+where
+    A: ArticleService,
+    U: UserService + Clone + Send + Sync + 'static,
+    C: CommentService,
+    F: FollowService,
+{
+    let viewer = auth_session.user.map(|user| user.name);
+    let article = match find_viewable_article(&state.article_service, &id, viewer.as_ref()).await {
+        Ok(article) => article,
+        Err(response) => return response,
+    };
+    match state
+        .comment_service
+        .get_comments(article.id, &article.author, viewer.as_ref())
+        .await
+    {
+        Ok(threads) => (StatusCode::OK, Json(threads)).into_response(),
+        Err(e) => comment_error_response(e),
+    }
+}
+
+/// ログイン中のユーザーとしてコメントを投稿する
+/// `parent_id`を指定した場合は、そのコメントへの返信になる
+/// 記事の著者の設定によっては、コメントは承認待ちになるか、受け付けられない
+pub async fn create_comment<A, U, C, F>(
+    State(state): State<AppState<A, U, C, F>>,
+    auth_session: AuthSession<U>,
+    Path(id): Path<String>,
+    Json(payload): Json<CreateCommentPayload>,
+) -> impl IntoResponse
+where
+    A: ArticleService,
+    U: UserService + Clone + Send + Sync + 'static,
+    C: CommentService,
+    F: FollowService,
+{
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let article = match find_viewable_article(&state.article_service, &id, Some(&user.name)).await {
+        Ok(article) => article,
+        Err(response) => return response,
+    };
+    let parent_id = match payload.parent_id.as_deref().map(CommentId::parse_str) {
+        None => None,
+        Some(Ok(parent_id)) => Some(parent_id),
+        Some(Err(_)) => {
+            return (StatusCode::BAD_REQUEST, "Invalid parent ID format").into_response();
+        }
+    };
+
+    // 著者が存在しない記事は、既定の設定で受け付ける
+    let moderation = match state
+        .user_service
+        .get_user_by_name(article.author.as_str())
+        .await
+    {
+        Ok(article_author) => article_author.comment_moderation,
+        Err(UserServiceError::UserNotFound) => CommentModeration::default(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    match state
+        .comment_service
+        .create_comment(
+            article.id,
+            article.author,
+            parent_id,
+            user.name,
+            payload.content,
+            moderation,
+        )
+        .await
+    {
+        Ok(comment) => (StatusCode::CREATED, Json(comment)).into_response(),
+        Err(e) => comment_error_response(e),
+    }
+}
+
+/// コメントの内容を更新する　著者の確認は`require_comment_author`で行う
+pub async fn update_comment<A, U, C, F>(
+    State(state): State<AppState<A, U, C, F>>,
+    Path((_, comment_id)): Path<(String, String)>,
+    Json(payload): Json<UpdateCommentPayload>,
+) -> impl IntoResponse
+where
+    A: ArticleService,
+    U: UserService,
+    C: CommentService,
+    F: FollowService,
+{
+    let Ok(oid) = CommentId::parse_str(&comment_id) else {
+        return (StatusCode::BAD_REQUEST, "Invalid ID format").into_response();
+    };
+    match state
+        .comment_service
+        .update_comment(oid, payload.content)
+        .await
+    {
+        Ok(comment) => (StatusCode::OK, Json(comment)).into_response(),
+        Err(e) => comment_error_response(e),
+    }
+}
+
+/// コメントを削除する　返信が付いている場合は、内容のみを消去してスレッドを残す
+pub async fn delete_comment<A, U, C, F>(
+    State(state): State<AppState<A, U, C, F>>,
+    Path((_, comment_id)): Path<(String, String)>,
+) -> impl IntoResponse
+where
+    A: ArticleService,
+    U: UserService,
+    C: CommentService,
+    F: FollowService,
+{
+    let Ok(oid) = CommentId::parse_str(&comment_id) else {
+        return (StatusCode::BAD_REQUEST, "Invalid ID format").into_response();
+    };
+    match state.comment_service.delete_comment(oid).await {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => comment_error_response(e),
+    }
+}
+
+/// ログイン中のユーザーの記事への、承認待ちのコメントを古い順に返す
+pub async fn get_moderation_queue<A, U, C, F>(
+    State(state): State<AppState<A, U, C, F>>,
+    auth_session: AuthSession<U>,
+    Query(params): Query<ModerationParams>,
+) -> impl IntoResponse
+where
+    A: ArticleService,
+    U: UserService + Clone + Send + Sync + 'static,
+    C: CommentService,
+    F: FollowService,
+{
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    match state
+        .comment_service
+        .get_pending_comments(&user.name, params.skip, params.limit)
+        .await
+    {
+        Ok(comments) => (StatusCode::OK, Json(comments)).into_response(),
+        Err(e) => comment_error_response(e),
+    }
+}
+
+/// 自分の記事へのコメントを承認する
+pub async fn approve_comment<A, U, C, F>(
+    State(state): State<AppState<A, U, C, F>>,
+    auth_session: AuthSession<U>,
+    Path(comment_id): Path<String>,
+) -> impl IntoResponse
+where
+    A: ArticleService,
+    U: UserService + Clone + Send + Sync + 'static,
+    C: CommentService,
+    F: FollowService,
+{
+    moderate_comment(&state, auth_session, &comment_id, CommentStatus::Approved).await
+}
+
+/// 自分の記事へのコメントを却下する　却下されたコメントはコメントの著者にのみ表示される
+pub async fn reject_comment<A, U, C, F>(
+    State(state): State<AppState<A, U, C, F>>,
+    auth_session: AuthSession<U>,
+    Path(comment_id): Path<String>,
+) -> impl IntoResponse
+where
+    A: ArticleService,
+    U: UserService + Clone + Send + Sync + 'static,
+    C: CommentService,
+    F: FollowService,
+{
+    moderate_comment(&state, auth_session, &comment_id, CommentStatus::Rejected).await
+}
+
+/// コメントの承認状態を変更する
+/// 未ログインの場合は`401`、コメントされた記事の著者でない場合は`403`を返す
+async fn moderate_comment<A, U, C, F>(
+    state: &AppState<A, U, C, F>,
+    auth_session: AuthSession<U>,
+    comment_id: &str,
+    status: CommentStatus,
+) -> Response
+where
+    A: ArticleService,
+    U: UserService + Clone + Send + Sync + 'static,
+    C: CommentService,
+    F: FollowService,
+{
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let Ok(oid) = CommentId::parse_str(comment_id) else {
+        return (StatusCode::BAD_REQUEST, "Invalid ID format").into_response();
+    };
+    match state.comment_service.get_comment_by_id(oid).await {
+        Ok(comment) if comment.article_author.as_ref() == Some(&user.name) => {}
+        Ok(_) => return StatusCode::FORBIDDEN.into_response(),
+        Err(e) => return comment_error_response(e),
+    }
+    match state.comment_service.set_comment_status(oid, status).await {
+        Ok(comment) => (StatusCode::OK, Json(comment)).into_response(),
+        Err(e) => comment_error_response(e),
+    }
+}
@@ -0,0 +1,138 @@
+use std::fmt::Display;
+
+use axum::{
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::Response,
+};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+
+/// 単一のリソースの強いETagを返す
+/// IDと更新日時から作成し、更新日時の精度で区別できない変更に備えてバージョンも含める
+pub fn strong_etag(id: impl Display, updated_at: DateTime<Utc>, version: u32) -> HeaderValue {
+    HeaderValue::from_str(&format!(
+        "\"{id}-{}-{version}\"",
+        updated_at.timestamp_micros()
+    ))
+    .unwrap()
+}
+
+/// 閲覧しているユーザーによって表現が変わるリソースの強いETagを返す
+/// `variant`が空の場合は`strong_etag`と同じになる
+pub fn strong_etag_with_variant(
+    id: impl Display,
+    updated_at: DateTime<Utc>,
+    version: u32,
+    variant: &str,
+) -> HeaderValue {
+    if variant.is_empty() {
+        return strong_etag(id, updated_at, version);
+    }
+    HeaderValue::from_str(&format!(
+        "\"{id}-{}-{version}-{variant}\"",
+        updated_at.timestamp_micros()
+    ))
+    .unwrap()
+}
+
+/// 閲覧しているユーザーによって表現が変わるレスポンスに、キャッシュを分けるためのヘッダを加える
+/// ログインしている場合は、共有キャッシュに保存されないように`Cache-Control: private`も加える
+pub fn vary_by_viewer(response: &mut Response, authenticated: bool) {
+    let headers = response.headers_mut();
+    headers.insert(header::VARY, HeaderValue::from_static("Cookie"));
+    if authenticated {
+        headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("private"));
+    }
+}
+
+/// 一覧の弱いETagを返す
+/// `items`には各リソースの強いETagを一覧の順に渡す
+pub fn weak_etag<'a>(items: impl IntoIterator<Item = &'a HeaderValue>) -> HeaderValue {
+    let mut hasher = Sha256::new();
+    for item in items {
+        hasher.update(item.as_bytes());
+        hasher.update(b",");
+    }
+    digest_etag(hasher)
+}
+
+/// レスポンスの本文から弱いETagを返す
+/// 記事以外の情報（著者の表示名など）も表現に含まれ、記事のETagからは変更を判断できない場合に使用する
+pub fn body_etag(body: &[u8]) -> HeaderValue {
+    digest_etag(Sha256::new().chain_update(body))
+}
+
+fn digest_etag(hasher: Sha256) -> HeaderValue {
+    let digest = hasher.finalize();
+    let hex: String = digest[..16].iter().map(|b| format!("{b:02x}")).collect();
+    HeaderValue::from_str(&format!("W/\"{hex}\"")).unwrap()
+}
+
+/// `Last-Modified`ヘッダに使用するHTTP-date形式に変換する
+pub fn http_date(date: DateTime<Utc>) -> HeaderValue {
+    HeaderValue::from_str(&date.format("%a, %d %b %Y %H:%M:%S GMT").to_string()).unwrap()
+}
+
+/// `If-None-Match`と`If-Modified-Since`を評価し、クライアントのキャッシュが最新であれば`true`を返す
+/// `If-None-Match`がある場合は`If-Modified-Since`を無視する
+/// `last_modified`が`None`の場合は`If-Modified-Since`を評価しない
+pub fn is_not_modified(
+    headers: &HeaderMap,
+    etag: &HeaderValue,
+    last_modified: Option<DateTime<Utc>>,
+) -> bool {
+    if let Some(if_none_match) = headers.get(header::IF_NONE_MATCH) {
+        let Ok(if_none_match) = if_none_match.to_str() else {
+            return false;
+        };
+        // 弱い比較を行うため、`W/`を取り除いて比較する
+        let etag = opaque_tag(etag.to_str().unwrap_or_default());
+        return if_none_match
+            .split(',')
+            .map(str::trim)
+            .any(|tag| tag == "*" || opaque_tag(tag) == etag);
+    }
+
+    if let Some(last_modified) = last_modified
+        && let Some(if_modified_since) = headers.get(header::IF_MODIFIED_SINCE)
+        && let Ok(if_modified_since) = if_modified_since.to_str()
+        && let Ok(since) = DateTime::parse_from_rfc2822(if_modified_since)
+    {
+        // HTTP-dateは秒単位のため、更新日時も秒単位で比較する
+        return last_modified.timestamp() <= since.timestamp();
+    }
+    false
+}
+
+fn opaque_tag(tag: &str) -> &str {
+    tag.strip_prefix("W/").unwrap_or(tag)
+}
+
+/// `If-Match`ヘッダを現在のリソースのETagと比較し、更新・削除の前提となるバージョンを返す
+/// `etags`には現在のリソースのETag（`strong_etag`）を、`version`には現在のバージョンを渡す
+/// ヘッダがない場合は`Ok(None)`を、一致した場合（`*`を含む）は`Ok(Some(version))`を返す
+/// 比較した後に更新された場合は、返したバージョンが一致しないことでリポジトリが検出する
+///
+/// # Errors
+/// リスト中のいずれのETagも一致しない場合は`412`を返す　強い比較を行うため、弱いETagは一致しない
+pub fn if_match_version(
+    headers: &HeaderMap,
+    etags: &[HeaderValue],
+    version: u32,
+) -> Result<Option<u32>, StatusCode> {
+    let Some(if_match) = headers.get(header::IF_MATCH) else {
+        return Ok(None);
+    };
+    let if_match = if_match
+        .to_str()
+        .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
+    if if_match
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || etags.iter().any(|etag| etag.as_bytes() == tag.as_bytes()))
+    {
+        Ok(Some(version))
+    } else {
+        Err(StatusCode::PRECONDITION_FAILED)
+    }
+}
@@ -1,44 +1,164 @@
-use axum::{Router, routing::get};
+use axum::{
+    Router,
+    middleware::from_fn_with_state,
+    routing::{get, patch, post},
+};
+use axum_login::{
+    AuthManagerLayerBuilder,
+    tower_sessions::{Expiry, SessionManagerLayer, SessionStore, cookie::time::Duration},
+};
 
 use crate::{
-    domain::models::{article_service::ArticleService, user_service::UserService},
-    presentation::handlers::{article_handler::*, user_handler::*},
+    domain::models::{
+        article_service::ArticleService, comment_service::CommentService,
+        follow_service::FollowService, user_service::UserService,
+    },
+    presentation::{
+        auth::AuthBackend,
+        handlers::{
+            article_handler::*, auth_handler::*, auth_middleware::*, comment_handler::*,
+            follow_handler::*, user_handler::*,
+        },
+    },
 };
 
 #[derive(Clone)]
-pub struct AppState<A: ArticleService, U: UserService> {
+pub struct AppState<A: ArticleService, U: UserService, C: CommentService, F: FollowService> {
     pub article_service: A,
     pub user_service: U,
+    pub comment_service: C,
+    pub follow_service: F,
 }
 
-pub fn create_handler<A, U>(article_service: A, user_service: U) -> Router
+/// `session_store`にはセッションの保存先を指定する
+/// 本番環境ではMongoDBに、テストではメモリ上に保存するストアを渡す
+pub fn create_handler<A, U, C, F, S>(
+    article_service: A,
+    user_service: U,
+    comment_service: C,
+    follow_service: F,
+    session_store: S,
+) -> Router
 where
     A: ArticleService + Clone + Send + Sync + 'static,
     U: UserService + Clone + Send + Sync + 'static,
+    C: CommentService + Clone + Send + Sync + 'static,
+    F: FollowService + Clone + Send + Sync + 'static,
+    S: SessionStore + Clone,
 {
+    let session_layer = SessionManagerLayer::new(session_store)
+        .with_expiry(Expiry::OnInactivity(Duration::days(7)));
+    let auth_layer =
+        AuthManagerLayerBuilder::new(AuthBackend::new(user_service.clone()), session_layer).build();
+
     let app_state = AppState {
         article_service,
         user_service,
+        comment_service,
+        follow_service,
     };
 
+    // 更新・削除は本人のみが行えるように、認可を行うミドルウェアを挟む
+    let owned_article_routes = patch(update_article::<A, U, C, F>)
+        .delete(delete_article::<A, U, C, F>)
+        .route_layer(from_fn_with_state(
+            app_state.clone(),
+            require_article_owner::<A, U, C, F>,
+        ));
+    let own_comment_routes = patch(update_comment::<A, U, C, F>)
+        .delete(delete_comment::<A, U, C, F>)
+        .route_layer(from_fn_with_state(
+            app_state.clone(),
+            require_comment_author::<A, U, C, F>,
+        ));
+    let own_user_routes = patch(update_user::<A, U, C, F>)
+        .delete(delete_user::<A, U, C, F>)
+        .route_layer(from_fn_with_state(app_state.clone(), require_self::<U>));
+
     Router::new()
         .route(
             "/articles",
-            get(get_articles::<A, U>).post(create_article::<A, U>),
+            get(get_articles::<A, U, C, F>).post(create_article::<A, U, C, F>),
         )
         .route(
             "/articles/{id}",
-            get(get_article_by_id::<A, U>)
-                .patch(update_article::<A, U>)
-                .delete(delete_article::<A, U>),
+            get(get_article_by_id::<A, U, C, F>).merge(owned_article_routes),
+        )
+        .route("/articles/search", get(search_articles::<A, U, C, F>))
+        .route("/articles/{id}/revisions", get(get_revisions::<A, U, C, F>))
+        .route(
+            "/articles/{id}/revisions/{rev}",
+            get(get_revision::<A, U, C, F>),
+        )
+        .route(
+            "/articles/{id}/revisions/{rev}/restore",
+            post(restore_revision::<A, U, C, F>).route_layer(from_fn_with_state(
+                app_state.clone(),
+                require_article_owner::<A, U, C, F>,
+            )),
+        )
+        .route("/articles/{id}/diff", get(diff_revisions::<A, U, C, F>))
+        .route(
+            "/articles/{id}/reactions/{kind}",
+            post(add_reaction::<A, U, C, F>).delete(remove_reaction::<A, U, C, F>),
+        )
+        .route(
+            "/articles/{id}/comments",
+            get(get_comments::<A, U, C, F>).post(create_comment::<A, U, C, F>),
+        )
+        .route("/articles/{id}/comments/{comment_id}", own_comment_routes)
+        .route("/me/moderation", get(get_moderation_queue::<A, U, C, F>))
+        .route(
+            "/me/moderation/{comment_id}/approve",
+            post(approve_comment::<A, U, C, F>),
+        )
+        .route(
+            "/me/moderation/{comment_id}/reject",
+            post(reject_comment::<A, U, C, F>),
+        )
+        .route("/tags", get(get_tags::<A, U, C, F>))
+        .route("/tags/{tag}/articles", get(get_tag_articles::<A, U, C, F>))
+        .route(
+            "/users",
+            get(list_users::<A, U, C, F>).post(create_user::<A, U, C, F>),
         )
-        .route("/articles/search", get(search_articles::<A, U>))
-        .route("/users", get(list_users::<A, U>).post(create_user::<A, U>))
         .route(
             "/users/{user_name}",
-            get(get_user::<A, U>)
-                .patch(update_user::<A, U>)
-                .delete(delete_user::<A, U>),
+            get(get_user::<A, U, C, F>).merge(own_user_routes),
+        )
+        .route(
+            "/users/{user_name}/follow",
+            post(follow_user::<A, U, C, F>).delete(unfollow_user::<A, U, C, F>),
+        )
+        .route(
+            "/users/{user_name}/followers",
+            get(get_followers::<A, U, C, F>),
+        )
+        .route(
+            "/users/{user_name}/following",
+            get(get_following::<A, U, C, F>),
+        )
+        .route(
+            "/users/{user_name}/articles",
+            get(get_user_articles::<A, U, C, F>),
+        )
+        .route(
+            "/users/{user_name}/stats",
+            get(get_user_stats::<A, U, C, F>),
+        )
+        .route(
+            "/users/{user_name}/articles/{slug}",
+            get(get_article_by_slug::<A, U, C, F>),
+        )
+        .route("/timeline", get(get_timeline::<A, U, C, F>))
+        .route(
+            "/users/{user_name}/drafts",
+            get(get_drafts::<A, U, C, F>)
+                .route_layer(from_fn_with_state(app_state.clone(), require_self::<U>)),
         )
+        .route("/auth/login", post(login::<U>))
+        .route("/auth/logout", post(logout::<U>))
+        .route("/auth/me", get(me::<U>))
         .with_state(app_state)
+        .layer(auth_layer)
 }
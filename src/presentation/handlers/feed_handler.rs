@@ -0,0 +1,418 @@
+use std::sync::Arc;
+
+use axum::{
+    Router,
+    extract::{Path, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+
+use crate::{
+    domain::models::{
+        article::{Article, ArticleId},
+        article_query::{ArticleQuery, ArticleSort},
+        article_service::ArticleService,
+        user_name::UserName,
+        user_service::{UserService, UserServiceError},
+    },
+    presentation::handlers::{conditional::*, sitemap_handler::*},
+};
+
+/// フィードに含める記事の数
+const FEED_ITEMS: usize = 20;
+
+/// フィードや記事のIDの接頭辞
+/// サイトのURLが変わってもIDが変わらないように、URLとは独立したtag URIを使用する
+const ID_PREFIX: &str = "tag:minweb2025-blog,2025:";
+
+const RSS_CONTENT_TYPE: &str = "application/rss+xml; charset=utf-8";
+const ATOM_CONTENT_TYPE: &str = "application/atom+xml; charset=utf-8";
+const JSON_FEED_CONTENT_TYPE: &str = "application/feed+json; charset=utf-8";
+
+/// フィードの生成に使用するサイトの情報
+#[derive(Debug, Clone)]
+pub struct FeedConfig {
+    /// サイトのURL　末尾の`/`は含めない
+    pub site_url: String,
+    pub title: String,
+}
+
+impl FeedConfig {
+    /// 環境変数`SITE_URL`と`SITE_TITLE`から読み込む
+    /// 設定されていない場合はローカルの開発環境の値を使用する
+    pub fn from_env() -> Self {
+        let site_url =
+            std::env::var("SITE_URL").unwrap_or_else(|_| "http://localhost:3000".to_string());
+        FeedConfig {
+            site_url: site_url.trim_end_matches('/').to_string(),
+            title: std::env::var("SITE_TITLE").unwrap_or_else(|_| "MinWeb2025 Blog".to_string()),
+        }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.site_url)
+    }
+
+    /// 記事のURL　IDではなく、著者とスラッグで記事を表す
+    /// スラッグが割り当てられる前の記事は、IDで表す
+    pub fn article_url(&self, article: &Article) -> String {
+        if article.slug.is_empty() {
+            self.url(&format!("/api/articles/{}", article.id))
+        } else {
+            self.url(&format!(
+                "/api/users/{}/articles/{}",
+                article.author, article.slug
+            ))
+        }
+    }
+
+    pub fn user_url(&self, name: &UserName) -> String {
+        self.url(&format!("/api/users/{name}"))
+    }
+}
+
+/// 記事のフィードやエントリーの、変わることのないID
+fn article_guid(id: ArticleId) -> String {
+    format!("{ID_PREFIX}article:{id}")
+}
+
+#[derive(Clone)]
+pub struct FeedState<A: ArticleService, U: UserService> {
+    pub article_service: A,
+    pub user_service: U,
+    pub config: Arc<FeedConfig>,
+}
+
+/// フィードとサイトマップのルーティング　`/api`の外に配置する
+/// フィードはログインせずに購読されるため、公開記事のみを含める
+pub fn create_feed_handler<A, U>(article_service: A, user_service: U, config: FeedConfig) -> Router
+where
+    A: ArticleService + Clone + Send + Sync + 'static,
+    U: UserService + Clone + Send + Sync + 'static,
+{
+    Router::new()
+        .route("/feeds/articles.rss", get(get_rss_feed::<A, U>))
+        .route("/feeds/articles.atom", get(get_atom_feed::<A, U>))
+        .route(
+            "/users/{user_name}/feed.atom",
+            get(get_user_atom_feed::<A, U>),
+        )
+        .route("/feed.json", get(get_json_feed::<A, U>))
+        .route("/sitemap.xml", get(get_sitemap_index::<A, U>))
+        .route("/sitemaps/{file}", get(get_sitemap::<A, U>))
+        .with_state(FeedState {
+            article_service,
+            user_service,
+            config: Arc::new(config),
+        })
+}
+
+/// サイト全体の新着記事のRSS 2.0フィード
+pub async fn get_rss_feed<A: ArticleService, U: UserService>(
+    State(state): State<FeedState<A, U>>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    match state
+        .article_service
+        .get_articles(0, FEED_ITEMS, ArticleSort::Newest, None, None)
+        .await
+    {
+        Ok(articles) => feed_response(&headers, RSS_CONTENT_TYPE, rss_feed(&state, &articles)),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// サイト全体の新着記事のAtomフィード
+pub async fn get_atom_feed<A: ArticleService, U: UserService>(
+    State(state): State<FeedState<A, U>>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    match state
+        .article_service
+        .get_articles(0, FEED_ITEMS, ArticleSort::Newest, None, None)
+        .await
+    {
+        Ok(articles) => {
+            let meta = AtomMeta {
+                id: format!("{ID_PREFIX}feed:articles"),
+                title: state.config.title.clone(),
+                self_url: state.config.url("/feeds/articles.atom"),
+            };
+            feed_response(
+                &headers,
+                ATOM_CONTENT_TYPE,
+                atom_feed(&state, &meta, &articles),
+            )
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// サイト全体の新着記事のJSON Feed 1.1
+pub async fn get_json_feed<A: ArticleService, U: UserService>(
+    State(state): State<FeedState<A, U>>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    match state
+        .article_service
+        .get_articles(0, FEED_ITEMS, ArticleSort::Newest, None, None)
+        .await
+    {
+        Ok(articles) => feed_response(
+            &headers,
+            JSON_FEED_CONTENT_TYPE,
+            json_feed(&state, &articles),
+        ),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// 指定したユーザーの新着記事のAtomフィード
+pub async fn get_user_atom_feed<A: ArticleService, U: UserService>(
+    State(state): State<FeedState<A, U>>,
+    headers: HeaderMap,
+    Path(user_name): Path<String>,
+) -> impl IntoResponse {
+    let user = match state.user_service.get_user_by_name(&user_name).await {
+        Ok(user) => user,
+        Err(e @ UserServiceError::UserNotFound) => {
+            return (StatusCode::NOT_FOUND, e.to_string()).into_response();
+        }
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let query = ArticleQuery {
+        author: Some(user.name.to_string()),
+        sort: ArticleSort::Newest,
+        ..Default::default()
+    };
+    match state
+        .article_service
+        .search_articles(0, FEED_ITEMS, query, None)
+        .await
+    {
+        Ok(articles) => {
+            let meta = AtomMeta {
+                id: format!("{ID_PREFIX}feed:users:{}", user.name),
+                title: format!("{} - {}", user.display_name, state.config.title),
+                self_url: state.config.url(&format!("/users/{}/feed.atom", user.name)),
+            };
+            feed_response(
+                &headers,
+                ATOM_CONTENT_TYPE,
+                atom_feed(&state, &meta, &articles),
+            )
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// フィードを`ETag`付きで返す
+/// ユーザーのフィードのタイトルに表示名を含めるなど、記事以外の情報もフィードに含まれるため、
+/// 生成したフィードの本文から`ETag`を作成し、`If-None-Match`の条件を満たす場合は`304`を返す
+/// 記事の削除や非公開への変更は記事の更新日時の最大値に反映されず、`If-Modified-Since`では検出できないため、
+/// `Last-Modified`は付けない
+fn feed_response(headers: &HeaderMap, content_type: &'static str, body: String) -> Response {
+    let etag = body_etag(body.as_bytes());
+
+    let mut cache_headers = HeaderMap::new();
+    cache_headers.insert(header::ETAG, etag.clone());
+    if is_not_modified(headers, &etag, None) {
+        return (StatusCode::NOT_MODIFIED, cache_headers).into_response();
+    }
+    cache_headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    (StatusCode::OK, cache_headers, body).into_response()
+}
+
+fn rss_feed<A: ArticleService, U: UserService>(
+    state: &FeedState<A, U>,
+    articles: &[Article],
+) -> String {
+    let config = &state.config;
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\" \
+         xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n<channel>\n",
+    );
+    xml += &format!("<title>{}</title>\n", escape(&config.title));
+    xml += &format!("<link>{}</link>\n", escape(&config.url("/")));
+    xml += &format!("<description>{}</description>\n", escape(&config.title));
+    xml += &format!(
+        "<atom:link href=\"{}\" rel=\"self\" type=\"application/rss+xml\"/>\n",
+        escape(&config.url("/feeds/articles.rss"))
+    );
+    if let Some(updated) = articles.iter().map(|article| article.updated_at).max() {
+        xml += &format!("<lastBuildDate>{}</lastBuildDate>\n", updated.to_rfc2822());
+    }
+    for article in articles {
+        xml += "<item>\n";
+        xml += &format!("<title>{}</title>\n", escape(&article.title));
+        xml += &format!("<link>{}</link>\n", escape(&config.article_url(article)));
+        xml += &format!(
+            "<guid isPermaLink=\"false\">{}</guid>\n",
+            article_guid(article.id)
+        );
+        xml += &format!("<pubDate>{}</pubDate>\n", article.created_at.to_rfc2822());
+        xml += &format!(
+            "<dc:creator>{}</dc:creator>\n",
+            escape(article.author.as_str())
+        );
+        for tag in &article.tags {
+            xml += &format!("<category>{}</category>\n", escape(tag));
+        }
+        xml += &format!(
+            "<description>{}</description>\n",
+            escape(&state.article_service.render_content_html(article))
+        );
+        xml += "</item>\n";
+    }
+    xml += "</channel>\n</rss>\n";
+    xml
+}
+
+/// Atomフィードの表題やリンク
+struct AtomMeta {
+    id: String,
+    title: String,
+    self_url: String,
+}
+
+fn atom_feed<A: ArticleService, U: UserService>(
+    state: &FeedState<A, U>,
+    meta: &AtomMeta,
+    articles: &[Article],
+) -> String {
+    let config = &state.config;
+    // `updated`は必須のため、記事がない場合は固定の日時とする
+    let updated = articles
+        .iter()
+        .map(|article| article.updated_at)
+        .max()
+        .unwrap_or(DateTime::UNIX_EPOCH);
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <feed xmlns=\"http://www.w3.org/2005/Atom\">\n",
+    );
+    xml += &format!("<id>{}</id>\n", escape(&meta.id));
+    xml += &format!("<title>{}</title>\n", escape(&meta.title));
+    xml += &format!("<updated>{}</updated>\n", atom_date(updated));
+    xml += &format!(
+        "<link rel=\"self\" type=\"application/atom+xml\" href=\"{}\"/>\n",
+        escape(&meta.self_url)
+    );
+    xml += &format!(
+        "<link rel=\"alternate\" href=\"{}\"/>\n",
+        escape(&config.url("/"))
+    );
+    for article in articles {
+        xml += "<entry>\n";
+        xml += &format!("<id>{}</id>\n", article_guid(article.id));
+        xml += &format!("<title>{}</title>\n", escape(&article.title));
+        xml += &format!(
+            "<link rel=\"alternate\" href=\"{}\"/>\n",
+            escape(&config.article_url(article))
+        );
+        xml += &format!("<published>{}</published>\n", atom_date(article.created_at));
+        xml += &format!("<updated>{}</updated>\n", atom_date(article.updated_at));
+        xml += &format!(
+            "<author><name>{}</name></author>\n",
+            escape(article.author.as_str())
+        );
+        for tag in &article.tags {
+            xml += &format!("<category term=\"{}\"/>\n", escape(tag));
+        }
+        xml += &format!(
+            "<content type=\"html\">{}</content>\n",
+            escape(&state.article_service.render_content_html(article))
+        );
+        xml += "</entry>\n";
+    }
+    xml += "</feed>\n";
+    xml
+}
+
+/// JSON Feed 1.1（https://www.jsonfeed.org/version/1.1/）
+#[derive(Serialize, Debug)]
+struct JsonFeed {
+    version: &'static str,
+    title: String,
+    home_page_url: String,
+    feed_url: String,
+    items: Vec<JsonFeedItem>,
+}
+
+#[derive(Serialize, Debug)]
+struct JsonFeedItem {
+    id: String,
+    url: String,
+    title: String,
+    content_html: String,
+    date_published: String,
+    date_modified: String,
+    authors: Vec<JsonFeedAuthor>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+struct JsonFeedAuthor {
+    name: String,
+}
+
+fn json_feed<A: ArticleService, U: UserService>(
+    state: &FeedState<A, U>,
+    articles: &[Article],
+) -> String {
+    let config = &state.config;
+    let feed = JsonFeed {
+        version: "https://jsonfeed.org/version/1.1",
+        title: config.title.clone(),
+        home_page_url: config.url("/"),
+        feed_url: config.url("/feed.json"),
+        items: articles
+            .iter()
+            .map(|article| JsonFeedItem {
+                id: article_guid(article.id),
+                url: config.article_url(article),
+                title: article.title.clone(),
+                content_html: state
+                    .article_service
+                    .render_content_html(article)
+                    .to_string(),
+                date_published: atom_date(article.created_at),
+                date_modified: atom_date(article.updated_at),
+                authors: vec![JsonFeedAuthor {
+                    name: article.author.to_string(),
+                }],
+                tags: article.tags.clone(),
+            })
+            .collect(),
+    };
+    serde_json::to_string(&feed).unwrap()
+}
+
+/// Atomの日時の形式（RFC 3339）に変換する
+pub fn atom_date(date: DateTime<Utc>) -> String {
+    date.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// XMLのテキストや属性値としてエスケープする
+/// XMLで使用できない制御文字は取り除く
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,233 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    domain::models::{
+        article_service::ArticleService,
+        comment_service::CommentService,
+        follow::Follow,
+        follow_service::{FollowService, FollowServiceError},
+        page_cursor::PageCursor,
+        user_service::UserService,
+    },
+    presentation::{
+        auth::AuthSession,
+        handlers::{
+            article_handler::{
+                ArticleResponse, ContentFormat, with_content_html, with_my_reactions,
+            },
+            create_handler::AppState,
+            util::*,
+        },
+    },
+};
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct FollowListParams {
+    #[serde(default = "default_skip")]
+    skip: usize,
+    #[serde(default = "default_limit")]
+    limit: usize,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TimelineParams {
+    /// 前のページの`next_cursor`　省略した場合は最新の記事から取得する
+    cursor: Option<String>,
+    #[serde(default = "default_limit")]
+    limit: usize,
+    /// `html`の場合は本文をHTMLに変換した`content_html`を加える
+    #[serde(default)]
+    format: ContentFormat,
+}
+
+/// フォロー・フォロワーの一覧の項目
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FollowResponse {
+    pub name: String,
+    pub followed_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TimelineResponse {
+    pub items: Vec<ArticleResponse>,
+    /// 次のページを取得するためのカーソル　次のページがない場合は`None`
+    pub next_cursor: Option<String>,
+}
+
+fn follow_error_response(e: FollowServiceError) -> Response {
+    match e {
+        FollowServiceError::UserNotFound => (StatusCode::NOT_FOUND, e.to_string()).into_response(),
+        FollowServiceError::CannotFollowSelf => {
+            (StatusCode::BAD_REQUEST, e.to_string()).into_response()
+        }
+        _ => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// ログイン中のユーザーとして`user_name`をフォローする
+pub async fn follow_user<A, U, C, F>(
+    State(state): State<AppState<A, U, C, F>>,
+    auth_session: AuthSession<U>,
+    Path(user_name): Path<String>,
+) -> impl IntoResponse
+where
+    A: ArticleService,
+    U: UserService + Clone + Send + Sync + 'static,
+    C: CommentService,
+    F: FollowService,
+{
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    match state.follow_service.follow(&user.name, &user_name).await {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => follow_error_response(e),
+    }
+}
+
+/// ログイン中のユーザーによる`user_name`のフォローを解除する
+pub async fn unfollow_user<A, U, C, F>(
+    State(state): State<AppState<A, U, C, F>>,
+    auth_session: AuthSession<U>,
+    Path(user_name): Path<String>,
+) -> impl IntoResponse
+where
+    A: ArticleService,
+    U: UserService + Clone + Send + Sync + 'static,
+    C: CommentService,
+    F: FollowService,
+{
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    match state.follow_service.unfollow(&user.name, &user_name).await {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => follow_error_response(e),
+    }
+}
+
+/// `user_name`のフォロワーを、フォローされた日時の新しい順に返す
+pub async fn get_followers<A, U, C, F>(
+    State(state): State<AppState<A, U, C, F>>,
+    Path(user_name): Path<String>,
+    Query(params): Query<FollowListParams>,
+) -> impl IntoResponse
+where
+    A: ArticleService,
+    U: UserService,
+    C: CommentService,
+    F: FollowService,
+{
+    match state
+        .follow_service
+        .get_followers(&user_name, params.skip, params.limit)
+        .await
+    {
+        Ok(follows) => {
+            let followers: Vec<FollowResponse> = follows
+                .into_iter()
+                .map(
+                    |Follow {
+                         follower,
+                         created_at,
+                         ..
+                     }| FollowResponse {
+                        name: follower.to_string(),
+                        followed_at: created_at,
+                    },
+                )
+                .collect();
+            (StatusCode::OK, Json(followers)).into_response()
+        }
+        Err(e) => follow_error_response(e),
+    }
+}
+
+/// `user_name`がフォローしているユーザーを、フォローした日時の新しい順に返す
+pub async fn get_following<A, U, C, F>(
+    State(state): State<AppState<A, U, C, F>>,
+    Path(user_name): Path<String>,
+    Query(params): Query<FollowListParams>,
+) -> impl IntoResponse
+where
+    A: ArticleService,
+    U: UserService,
+    C: CommentService,
+    F: FollowService,
+{
+    match state
+        .follow_service
+        .get_following(&user_name, params.skip, params.limit)
+        .await
+    {
+        Ok(follows) => {
+            let following: Vec<FollowResponse> = follows
+                .into_iter()
+                .map(
+                    |Follow {
+                         followee,
+                         created_at,
+                         ..
+                     }| FollowResponse {
+                        name: followee.to_string(),
+                        followed_at: created_at,
+                    },
+                )
+                .collect();
+            (StatusCode::OK, Json(following)).into_response()
+        }
+        Err(e) => follow_error_response(e),
+    }
+}
+
+/// ログイン中のユーザーがフォローしているユーザーの記事を新しい順に返す
+/// 続きは`next_cursor`を`cursor`に指定して取得する
+pub async fn get_timeline<A, U, C, F>(
+    State(state): State<AppState<A, U, C, F>>,
+    auth_session: AuthSession<U>,
+    Query(params): Query<TimelineParams>,
+) -> impl IntoResponse
+where
+    A: ArticleService,
+    U: UserService + Clone + Send + Sync + 'static,
+    C: CommentService,
+    F: FollowService,
+{
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let cursor = match parse_cursor(params.cursor.as_deref()) {
+        Ok(cursor) => cursor,
+        Err(status) => return (status, "Invalid cursor").into_response(),
+    };
+
+    let articles = match state
+        .follow_service
+        .get_timeline(&user.name, cursor, params.limit)
+        .await
+    {
+        Ok(articles) => articles,
+        Err(e) => return follow_error_response(e),
+    };
+    let next_cursor = next_cursor(&articles, params.limit, |article| {
+        PageCursor::new(article.created_at, article.id)
+    });
+    match with_my_reactions(&state.article_service, articles, Some(&user.name)).await {
+        Ok(items) => {
+            let items = with_content_html(&state.article_service, items, params.format);
+            (
+                StatusCode::OK,
+                Json(TimelineResponse { items, next_cursor }),
+            )
+                .into_response()
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
@@ -1,4 +1,11 @@
 pub mod article_handler;
+pub mod auth_handler;
+pub mod auth_middleware;
+pub mod comment_handler;
+pub mod conditional;
 pub mod create_handler;
+pub mod feed_handler;
+pub mod follow_handler;
+pub mod sitemap_handler;
 pub mod user_handler;
 pub mod util;
@@ -0,0 +1,283 @@
+use std::sync::Arc;
+
+use axum::{
+    BoxError,
+    body::Body,
+    extract::{Path, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use futures::{StreamExt, stream};
+
+use crate::{
+    domain::models::{
+        article_query::ArticleSort,
+        article_service::{ArticleService, ArticleServiceError},
+        page_cursor::PageCursor,
+        user_service::{UserService, UserServiceError},
+    },
+    presentation::handlers::feed_handler::{FeedState, atom_date, escape},
+};
+
+/// 1つのサイトマップに含めるURLの最大数　サイトマップの仕様による上限
+pub const URLS_PER_SITEMAP: usize = 50_000;
+
+/// サイトマップを生成するときに、一度にリポジトリから取得する件数
+const SITEMAP_BATCH: usize = 1000;
+
+const SITEMAP_CONTENT_TYPE: &str = "application/xml; charset=utf-8";
+
+/// サイトマップに含める1つのURL
+struct SitemapEntry {
+    loc: String,
+    lastmod: DateTime<Utc>,
+    /// 続きを取得するためのカーソル
+    cursor: PageCursor,
+}
+
+/// 項目の数から、サイトマップの数を求める
+fn sitemap_count(count: u64) -> usize {
+    (count as usize).div_ceil(URLS_PER_SITEMAP)
+}
+
+/// サイトマップインデックス
+/// 公開記事とユーザーのプロフィールを、それぞれ`URLS_PER_SITEMAP`件ごとのサイトマップに分割して列挙する
+pub async fn get_sitemap_index<A: ArticleService, U: UserService>(
+    State(state): State<FeedState<A, U>>,
+) -> impl IntoResponse {
+    let articles = match state.article_service.count_articles(None).await {
+        Ok(count) => count,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let users = match state.user_service.count_users().await {
+        Ok(count) => count,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <sitemapindex xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+    );
+    for (kind, count) in [("articles", articles), ("users", users)] {
+        for page in 1..=sitemap_count(count) {
+            let loc = state.config.url(&format!("/sitemaps/{kind}-{page}.xml"));
+            xml += &format!("<sitemap><loc>{}</loc></sitemap>\n", escape(&loc));
+        }
+    }
+    xml += "</sitemapindex>\n";
+    ([(header::CONTENT_TYPE, SITEMAP_CONTENT_TYPE)], xml).into_response()
+}
+
+/// サイトマップの`file`（`articles-{page}.xml`または`users-{page}.xml`）を返す
+/// ルーティングではパスの一部だけを引数にできないため、ファイル名は自分で解析する
+pub async fn get_sitemap<A, U>(
+    State(state): State<FeedState<A, U>>,
+    Path(file): Path<String>,
+) -> Response
+where
+    A: ArticleService + Clone + Send + Sync + 'static,
+    U: UserService + Clone + Send + Sync + 'static,
+{
+    let Some((kind, page)) = file
+        .strip_suffix(".xml")
+        .and_then(|name| name.rsplit_once('-'))
+        .and_then(|(kind, page)| Some((kind, page.parse::<usize>().ok()?)))
+    else {
+        return (StatusCode::NOT_FOUND, "Sitemap not found").into_response();
+    };
+    match kind {
+        "articles" => article_sitemap(state, page).await,
+        "users" => user_sitemap(state, page).await,
+        _ => (StatusCode::NOT_FOUND, "Sitemap not found").into_response(),
+    }
+}
+
+/// 公開記事のサイトマップ　`page`は1から始まる
+async fn article_sitemap<A, U>(state: FeedState<A, U>, page: usize) -> Response
+where
+    A: ArticleService + Clone + Send + Sync + 'static,
+    U: UserService,
+{
+    let count = match state.article_service.count_articles(None).await {
+        Ok(count) => count,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    if page == 0 || page > sitemap_count(count) {
+        return (StatusCode::NOT_FOUND, "Sitemap not found").into_response();
+    }
+
+    let FeedState {
+        article_service,
+        config,
+        ..
+    } = state;
+    let body = sitemap_body((page - 1) * URLS_PER_SITEMAP, move |skip, limit, cursor| {
+        let article_service = article_service.clone();
+        let config = config.clone();
+        async move {
+            let articles = article_service
+                .get_articles(skip, limit, ArticleSort::Oldest, cursor, None)
+                .await?;
+            Ok::<_, ArticleServiceError>(
+                articles
+                    .into_iter()
+                    .map(|article| SitemapEntry {
+                        loc: config.article_url(&article),
+                        lastmod: article.updated_at,
+                        cursor: PageCursor::new(article.created_at, article.id),
+                    })
+                    .collect(),
+            )
+        }
+    });
+    ([(header::CONTENT_TYPE, SITEMAP_CONTENT_TYPE)], body).into_response()
+}
+
+/// ユーザーのプロフィールのサイトマップ　`page`は1から始まる
+async fn user_sitemap<A, U>(state: FeedState<A, U>, page: usize) -> Response
+where
+    A: ArticleService,
+    U: UserService + Clone + Send + Sync + 'static,
+{
+    let count = match state.user_service.count_users().await {
+        Ok(count) => count,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    if page == 0 || page > sitemap_count(count) {
+        return (StatusCode::NOT_FOUND, "Sitemap not found").into_response();
+    }
+
+    let FeedState {
+        user_service,
+        config,
+        ..
+    } = state;
+    let body = sitemap_body((page - 1) * URLS_PER_SITEMAP, move |skip, limit, cursor| {
+        let user_service = user_service.clone();
+        let config = config.clone();
+        async move {
+            let users = user_service.get_users(skip, limit, cursor).await?;
+            Ok::<_, UserServiceError>(
+                users
+                    .into_iter()
+                    .map(|user| SitemapEntry {
+                        loc: config.user_url(&user.name),
+                        lastmod: user.updated_at.unwrap_or(user.created_at),
+                        cursor: PageCursor::new(user.created_at, user.id),
+                    })
+                    .collect(),
+            )
+        }
+    });
+    ([(header::CONTENT_TYPE, SITEMAP_CONTENT_TYPE)], body).into_response()
+}
+
+/// サイトマップの本文を、リポジトリから`SITEMAP_BATCH`件ずつ取得しながらストリームで返す
+/// 最初のバッチは`skip`件を読み飛ばし、以降は直前のバッチの最後の項目のカーソルから取得するため、
+/// すべての項目をメモリに読み込むことはない
+/// 取得に失敗した場合はレスポンスを途中で打ち切る
+fn sitemap_body<F, Fut, E>(skip: usize, fetch: F) -> Body
+where
+    F: Fn(usize, usize, Option<PageCursor>) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<Vec<SitemapEntry>, E>> + Send + 'static,
+    E: Into<BoxError> + Send + 'static,
+{
+    let fetch = Arc::new(fetch);
+    // (読み飛ばす件数, カーソル, このサイトマップに含められる残りの件数)
+    let start = Some((skip, None, URLS_PER_SITEMAP));
+    let urls = stream::unfold(start, move |state| {
+        let fetch = fetch.clone();
+        async move {
+            let (skip, cursor, remaining) = state?;
+            let limit = remaining.min(SITEMAP_BATCH);
+            let entries = match fetch(skip, limit, cursor).await {
+                Ok(entries) => entries,
+                Err(e) => return Some((Err(e), None)),
+            };
+            let last = entries.last()?.cursor;
+            let next = (entries.len() == limit && remaining > limit).then_some((
+                0,
+                Some(last),
+                remaining - limit,
+            ));
+            let xml: String = entries
+                .iter()
+                .map(|entry| {
+                    format!(
+                        "<url><loc>{}</loc><lastmod>{}</lastmod></url>\n",
+                        escape(&entry.loc),
+                        atom_date(entry.lastmod)
+                    )
+                })
+                .collect();
+            Some((Ok(xml), next))
+        }
+    });
+    let header = stream::once(async {
+        Ok(String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+        ))
+    });
+    let footer = stream::once(async { Ok(String::from("</urlset>\n")) });
+    Body::from_stream(header.chain(urls).chain(footer))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use bson::oid::ObjectId;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn sitemap_is_fetched_in_batches_from_the_cursor() {
+        let base = Utc::now();
+        let items: Arc<Vec<PageCursor>> = Arc::new(
+            (0..2500)
+                .map(|i| PageCursor::new(base + chrono::Duration::seconds(i), ObjectId::new()))
+                .collect(),
+        );
+        let fetched = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let body = {
+            let fetched = fetched.clone();
+            sitemap_body(10, move |skip, limit, cursor: Option<PageCursor>| {
+                let items = items.clone();
+                fetched
+                    .lock()
+                    .unwrap()
+                    .push((skip, limit, cursor.is_some()));
+                async move {
+                    Ok::<_, Infallible>(
+                        items
+                            .iter()
+                            .filter(|item| {
+                                cursor
+                                    .is_none_or(|cursor| cursor.is_after(item.created_at, item.id))
+                            })
+                            .skip(skip)
+                            .take(limit)
+                            .map(|item| SitemapEntry {
+                                loc: format!("https://example.com/{}", item.id),
+                                lastmod: item.created_at,
+                                cursor: *item,
+                            })
+                            .collect(),
+                    )
+                }
+            })
+        };
+        let xml = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+        let xml = String::from_utf8(xml.to_vec()).unwrap();
+
+        // 最初のバッチのみ`skip`を使い、以降はカーソルから続きを取得する
+        assert_eq!(
+            *fetched.lock().unwrap(),
+            [(10, 1000, false), (0, 1000, true), (0, 1000, true)]
+        );
+        assert_eq!(xml.matches("<url>").count(), 2490);
+        assert!(xml.ends_with("</urlset>\n"));
+    }
+}
@@ -1,19 +1,27 @@
 use crate::{
     domain::models::{
         article_service::ArticleService,
-        user::UserId,
+        author_stats::AuthorStats,
+        comment_service::CommentService,
+        follow_service::FollowService,
+        page_cursor::PageCursor,
+        user::{ArticleDeletionPolicy, CommentModeration, UserId},
         user_service::{UserService, UserServiceError},
     },
-    presentation::handlers::create_handler::AppState,
+    presentation::{auth::AuthSession, handlers::create_handler::AppState},
 };
 use axum::{
     Json,
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
 };
 use serde::{Deserialize, Serialize};
 
-use super::util::{default_limit, default_skip};
+use super::{
+    conditional::{http_date, if_match_version, is_not_modified, strong_etag, vary_by_viewer},
+    util::{NEXT_CURSOR, Page, default_limit, default_skip, next_cursor, parse_cursor},
+};
 
 #[derive(Deserialize)]
 pub struct CreateUserRequest {
@@ -32,6 +40,7 @@ pub struct UpdateUserRequest {
     pub email: Option<String>,
     pub show_email: Option<bool>,
     pub password: Option<String>,
+    pub comment_moderation: Option<CommentModeration>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -42,6 +51,8 @@ pub struct UserResponse {
     pub intro: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub email: Option<String>,
+    /// 記事へのコメントを受け付けるかどうかを、コメントする側が確認できるように公開する
+    pub comment_moderation: CommentModeration,
 }
 
 #[derive(Deserialize)]
@@ -50,12 +61,24 @@ pub struct GetUsersParams {
     skip: usize,
     #[serde(default = "default_limit")]
     limit: usize,
+    /// 前のページの`X-Next-Cursor`ヘッダの値
+    cursor: Option<String>,
+    /// `true`の場合は一覧を総数などとともにエンベロープ（`Page`）に包んで返す
+    #[serde(default)]
+    envelope: bool,
 }
 
-pub async fn create_user<A: ArticleService, U: UserService>(
-    State(state): State<AppState<A, U>>,
+pub async fn create_user<A: ArticleService, U: UserService, C: CommentService, F: FollowService>(
+    State(state): State<AppState<A, U, C, F>>,
     Json(payload): Json<CreateUserRequest>,
-) -> Result<(StatusCode, Json<UserResponse>), StatusCode> {
+) -> Result<
+    (
+        StatusCode,
+        [(header::HeaderName, HeaderValue); 1],
+        Json<UserResponse>,
+    ),
+    StatusCode,
+> {
     let user = state
         .user_service
         .create_user(
@@ -67,7 +90,12 @@ pub async fn create_user<A: ArticleService, U: UserService>(
             payload.password,
         )
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(|e| match e {
+            UserServiceError::UserAlreadyExists => StatusCode::CONFLICT,
+            UserServiceError::ReservedUserName => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+    let etag = strong_etag(user.id, user.last_modified(), user.version);
     let user_response = UserResponse {
         id: user.id,
         name: user.name.to_string(),
@@ -78,14 +106,21 @@ pub async fn create_user<A: ArticleService, U: UserService>(
         } else {
             None
         },
+        comment_moderation: user.comment_moderation,
     };
-    Ok((StatusCode::CREATED, Json(user_response)))
+    Ok((
+        StatusCode::CREATED,
+        [(header::ETAG, etag)],
+        Json(user_response),
+    ))
 }
 
-pub async fn get_user<A: ArticleService, U: UserService>(
-    State(state): State<AppState<A, U>>,
+/// 強いETagと`Last-Modified`を付け、`If-None-Match`か`If-Modified-Since`の条件を満たす場合は`304`を返す
+pub async fn get_user<A: ArticleService, U: UserService, C: CommentService, F: FollowService>(
+    State(state): State<AppState<A, U, C, F>>,
     Path(user_name): Path<String>,
-) -> Result<Json<UserResponse>, StatusCode> {
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
     let user = state
         .user_service
         .get_user_by_name(&user_name)
@@ -97,6 +132,14 @@ pub async fn get_user<A: ArticleService, U: UserService>(
                 StatusCode::INTERNAL_SERVER_ERROR
             }
         })?;
+    let etag = strong_etag(user.id, user.last_modified(), user.version);
+    let cache_headers = [
+        (header::ETAG, etag.clone()),
+        (header::LAST_MODIFIED, http_date(user.last_modified())),
+    ];
+    if is_not_modified(&headers, &etag, Some(user.last_modified())) {
+        return Ok((StatusCode::NOT_MODIFIED, cache_headers).into_response());
+    }
     let user_response = UserResponse {
         id: user.id,
         name: user.name.to_string(),
@@ -107,20 +150,89 @@ pub async fn get_user<A: ArticleService, U: UserService>(
         } else {
             None
         },
+        comment_moderation: user.comment_moderation,
+    };
+    Ok((cache_headers, Json(user_response)).into_response())
+}
+
+/// ユーザーの執筆についての統計を取得する
+/// 記事の一覧と同様に、閲覧しているユーザーの一覧に表示される記事のみを集計する
+pub async fn get_user_stats<A, U, C, F>(
+    State(state): State<AppState<A, U, C, F>>,
+    auth_session: AuthSession<U>,
+    Path(user_name): Path<String>,
+) -> impl IntoResponse
+where
+    A: ArticleService,
+    U: UserService + Clone + Send + Sync + 'static,
+    C: CommentService,
+    F: FollowService,
+{
+    let author = match state.user_service.get_user_by_name(&user_name).await {
+        Ok(author) => author.name,
+        Err(UserServiceError::UserNotFound) => {
+            return (StatusCode::NOT_FOUND, "User not found").into_response();
+        }
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let viewer = auth_session.user.map(|user| user.name);
+    let articles = match state
+        .article_service
+        .get_author_stats(author.as_str(), viewer.as_ref())
+        .await
+    {
+        Ok(articles) => articles,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     };
-    Ok(Json(user_response))
+    match state
+        .comment_service
+        .count_comments_by_articles(&articles.article_ids)
+        .await
+    {
+        Ok(total_comments) => {
+            let mut response = (
+                StatusCode::OK,
+                Json(AuthorStats::new(articles, total_comments)),
+            )
+                .into_response();
+            // 閲覧しているユーザーによって集計の対象の記事が変わる
+            vary_by_viewer(&mut response, viewer.is_some());
+            response
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
 }
 
-pub async fn list_users<A: ArticleService, U: UserService>(
-    State(state): State<AppState<A, U>>,
+/// 次のページがある場合は、そのカーソルを`X-Next-Cursor`ヘッダで返す
+pub async fn list_users<A: ArticleService, U: UserService, C: CommentService, F: FollowService>(
+    State(state): State<AppState<A, U, C, F>>,
     Query(params): Query<GetUsersParams>,
-) -> Result<Json<Vec<UserResponse>>, StatusCode> {
+) -> Result<Response, StatusCode> {
+    let cursor = parse_cursor(params.cursor.as_deref())?;
+    let total = if params.envelope {
+        Some(
+            state
+                .user_service
+                .count_users()
+                .await
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+        )
+    } else {
+        None
+    };
     let users = state
         .user_service
-        .get_users(params.skip, params.limit)
+        .get_users(params.skip, params.limit, cursor)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    let user_responses = users
+    let mut response_headers = HeaderMap::new();
+    if let Some(next_cursor) = next_cursor(&users, params.limit, |user| {
+        PageCursor::new(user.created_at, user.id)
+    }) && let Ok(value) = HeaderValue::from_str(&next_cursor)
+    {
+        response_headers.insert(NEXT_CURSOR, value);
+    }
+    let user_responses: Vec<UserResponse> = users
         .into_iter()
         .map(|user| UserResponse {
             id: user.id,
@@ -132,16 +244,51 @@ pub async fn list_users<A: ArticleService, U: UserService>(
             } else {
                 None
             },
+            comment_moderation: user.comment_moderation,
         })
         .collect();
-    Ok(Json(user_responses))
+    Ok(match total {
+        Some(total) => {
+            let page = Page::new(user_responses, total, params.skip, params.limit);
+            let page = if cursor.is_some() {
+                page.without_next()
+            } else {
+                page
+            };
+            (response_headers, Json(page)).into_response()
+        }
+        None => (response_headers, Json(user_responses)).into_response(),
+    })
+}
+
+/// `If-Match`ヘッダを指定した場合は、現在のユーザーのETagと比較して更新・削除の前提となるバージョンを返す
+async fn user_if_match<U: UserService>(
+    user_service: &U,
+    headers: &HeaderMap,
+    user_name: &str,
+) -> Result<Option<u32>, StatusCode> {
+    if !headers.contains_key(header::IF_MATCH) {
+        return Ok(None);
+    }
+    let user = user_service
+        .get_user_by_name(user_name)
+        .await
+        .map_err(|e| match e {
+            UserServiceError::UserNotFound => StatusCode::NOT_FOUND,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+    let etag = strong_etag(user.id, user.last_modified(), user.version);
+    if_match_version(headers, &[etag], user.version)
 }
 
-pub async fn update_user<A: ArticleService, U: UserService>(
-    State(state): State<AppState<A, U>>,
+/// `If-Match`ヘッダを指定した場合は、ユーザーのETagが一致するときのみ更新する
+pub async fn update_user<A: ArticleService, U: UserService, C: CommentService, F: FollowService>(
+    State(state): State<AppState<A, U, C, F>>,
     Path(user_name): Path<String>,
+    headers: HeaderMap,
     Json(payload): Json<UpdateUserRequest>,
-) -> Result<Json<UserResponse>, StatusCode> {
+) -> Result<([(header::HeaderName, HeaderValue); 1], Json<UserResponse>), StatusCode> {
+    let expected_version = user_if_match(&state.user_service, &headers, &user_name).await?;
     let user = state
         .user_service
         .update_user(
@@ -151,9 +298,15 @@ pub async fn update_user<A: ArticleService, U: UserService>(
             payload.email,
             payload.show_email,
             payload.password,
+            payload.comment_moderation,
+            expected_version,
         )
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(|e| match e {
+            UserServiceError::VersionMismatch => StatusCode::PRECONDITION_FAILED,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+    let etag = strong_etag(user.id, user.last_modified(), user.version);
     let user_response = UserResponse {
         id: user.id,
         name: user.name.to_string(),
@@ -164,16 +317,36 @@ pub async fn update_user<A: ArticleService, U: UserService>(
         } else {
             None
         },
+        comment_moderation: user.comment_moderation,
     };
-    Ok(Json(user_response))
+    Ok(([(header::ETAG, etag)], Json(user_response)))
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct DeleteUserParams {
+    #[serde(default)]
+    articles: ArticleDeletionPolicy,
 }
 
-pub async fn delete_user<A: ArticleService, U: UserService>(
-    State(state): State<AppState<A, U>>,
+/// `If-Match`ヘッダを指定した場合は、ユーザーのETagが一致するときのみ削除する
+/// 同じ名前で登録したユーザーが引き継がないように、`articles`に従って記事を処理してからユーザーを削除する
+/// `articles=reject`で記事がある場合は`409`を返す
+pub async fn delete_user<A: ArticleService, U: UserService, C: CommentService, F: FollowService>(
+    State(state): State<AppState<A, U, C, F>>,
     Path(user_name): Path<String>,
+    headers: HeaderMap,
+    Query(params): Query<DeleteUserParams>,
 ) -> Result<StatusCode, StatusCode> {
-    match state.user_service.delete_user(&user_name).await {
+    let expected_version = user_if_match(&state.user_service, &headers, &user_name).await?;
+    match state
+        .user_service
+        .delete_user(&user_name, expected_version, params.articles)
+        .await
+    {
         Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(UserServiceError::UserNotFound) => Err(StatusCode::NOT_FOUND),
+        Err(UserServiceError::VersionMismatch) => Err(StatusCode::PRECONDITION_FAILED),
+        Err(UserServiceError::HasArticles) => Err(StatusCode::CONFLICT),
         Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
 }
@@ -1,3 +1,11 @@
+use axum::http::{HeaderName, StatusCode};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::domain::models::page_cursor::PageCursor;
+
+/// 一覧の次のページを取得するためのカーソルを返すレスポンスヘッダ
+pub const NEXT_CURSOR: HeaderName = HeaderName::from_static("x-next-cursor");
+
 pub fn default_skip() -> usize {
     0
 }
@@ -5,3 +13,61 @@ pub fn default_skip() -> usize {
 pub fn default_limit() -> usize {
     100
 }
+
+/// 一覧をページの情報とともに返すエンベロープ　`envelope=true`を指定した場合に使用する
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// 条件に一致する項目の総数
+    pub total: u64,
+    pub skip: usize,
+    pub limit: usize,
+    /// 次のページを取得する場合に指定する`skip`　次のページがない場合は`None`
+    pub next: Option<usize>,
+}
+
+impl<T> Page<T> {
+    pub fn new(items: Vec<T>, total: u64, skip: usize, limit: usize) -> Self {
+        let end = skip + items.len();
+        let next = (!items.is_empty() && (end as u64) < total).then_some(end);
+        Page {
+            items,
+            total,
+            skip,
+            limit,
+            next,
+        }
+    }
+
+    /// カーソルで取得したページでは`skip`による続きを返さない
+    /// 総数はカーソルより前の項目も含むため、続きは`X-Next-Cursor`ヘッダで取得する
+    pub fn without_next(self) -> Self {
+        Page { next: None, ..self }
+    }
+}
+
+/// クエリパラメータのカーソルを読み込む　形式が正しくない場合は`400 Bad Request`を返す
+pub fn parse_cursor(cursor: Option<&str>) -> Result<Option<PageCursor>, StatusCode> {
+    cursor
+        .map(|cursor| PageCursor::decode(cursor).ok_or(StatusCode::BAD_REQUEST))
+        .transpose()
+}
+
+/// 上限まで取得できた場合のみ、次のページがあるものとして最後の項目のカーソルを返す
+pub fn next_cursor<T>(items: &[T], limit: usize, key: impl Fn(&T) -> PageCursor) -> Option<String> {
+    (items.len() == limit)
+        .then(|| items.last())
+        .flatten()
+        .map(|item| key(item).encode())
+}
+
+/// フィールドが省略された場合と`null`が指定された場合を区別するためのデシリアライザ
+/// `#[serde(default, deserialize_with = "deserialize_some")]`と組み合わせて使用し、
+/// 省略された場合は`None`、`null`の場合は`Some(None)`になる
+pub fn deserialize_some<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
@@ -1,7 +1,19 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 
-use crate::domain::models::article::ArticleId;
-use crate::domain::models::article_query::ArticleQuery;
+use crate::domain::models::article::{ArticleId, Visibility};
+use crate::domain::models::article_query::{ArticleQuery, ArticleSort};
+use crate::domain::models::article_revision::{ArticleRevision, RevisionDiff};
+use crate::domain::models::author_stats::AuthorArticleStats;
+use crate::domain::models::markdown::HtmlCache;
+use crate::domain::models::page_cursor::PageCursor;
+use crate::domain::models::reaction::ReactionKind;
+use crate::domain::models::search::{SearchHit, SearchResults, query_terms, snippet};
+use crate::domain::models::slug::{numbered_slug, slugify};
+use crate::domain::models::tag::{TagCount, normalize_tags};
 use crate::domain::{
     models::{
         article::Article, article_service::ArticleService, article_service::ArticleServiceError,
@@ -10,14 +22,95 @@ use crate::domain::{
     repositorys::article_repository::ArticleRepository,
 };
 
+/// スラッグの確認と保存の間に他の記事が同じスラッグを使用した場合に、スラッグを作り直して保存を試みる最大回数
+const MAX_SLUG_ATTEMPTS: usize = 3;
+
 #[derive(Clone)]
 pub struct ArticleUsecase<A: ArticleRepository + Clone> {
     repository: A,
+    html_cache: HtmlCache,
 }
 
 impl<A: ArticleRepository + Clone> ArticleUsecase<A> {
     pub fn new(repository: A) -> Self {
-        ArticleUsecase { repository }
+        ArticleUsecase {
+            repository,
+            html_cache: HtmlCache::default(),
+        }
+    }
+
+    /// タイトルから、著者の他の記事と重複しないスラッグを作る
+    /// 重複する場合は`-2`、`-3`…と番号を付ける　`id`の記事自身が使用しているスラッグは重複とみなさない
+    /// タイトルからスラッグを作れない場合は記事のIDから接尾辞を作る　作成時は追加する記事のIDを渡す
+    async fn unique_slug(
+        &self,
+        author: &str,
+        title: &str,
+        id: ArticleId,
+    ) -> Result<String, ArticleServiceError> {
+        let base = slugify(title, id);
+        for n in 1.. {
+            let slug = numbered_slug(&base, n);
+            match self.repository.get_article_by_slug(author, &slug).await {
+                Err(ArticleServiceError::ArticleNotFound) => return Ok(slug),
+                Ok(article) if article.id == id => return Ok(slug),
+                Ok(_) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        unreachable!()
+    }
+
+    /// タイトルが変わった場合に、新しいスラッグを作る　スラッグが変わらない場合は`None`を返す
+    async fn renamed_slug(
+        &self,
+        current: &Article,
+        title: Option<&str>,
+    ) -> Result<Option<String>, ArticleServiceError> {
+        let Some(title) = title.filter(|title| *title != current.title) else {
+            return Ok(None);
+        };
+        let slug = self
+            .unique_slug(current.author.as_str(), title, current.id)
+            .await?;
+        Ok((slug != current.slug).then_some(slug))
+    }
+
+    /// 記事を更新する　タイトルが変わった場合はスラッグも作り直す
+    /// スラッグの確認と更新の間に他の記事が同じスラッグを使用した場合は、スラッグを作り直して再試行する
+    #[allow(clippy::too_many_arguments)]
+    async fn update_with_slug(
+        &self,
+        current: &Article,
+        title: Option<String>,
+        content: Option<String>,
+        visibility: Option<Visibility>,
+        publish_at: Option<Option<DateTime<Utc>>>,
+        tags: Option<Vec<String>>,
+        expected_version: Option<u32>,
+    ) -> Result<Article, ArticleServiceError> {
+        for _ in 0..MAX_SLUG_ATTEMPTS {
+            let slug = self.renamed_slug(current, title.as_deref()).await?;
+            let renamed = slug.is_some();
+            match self
+                .repository
+                .update_article(
+                    current.id,
+                    title.clone(),
+                    slug,
+                    content.clone(),
+                    visibility,
+                    publish_at,
+                    tags.clone(),
+                    expected_version,
+                )
+                .await
+            {
+                Err(ArticleServiceError::ArticleAlreadyExists) if renamed => continue,
+                result => return result,
+            }
+        }
+        Err(ArticleServiceError::ArticleAlreadyExists)
     }
 }
 
@@ -27,21 +120,65 @@ impl<A: ArticleRepository + Clone + Send + Sync> ArticleService for ArticleUseca
         &self,
         skip: usize,
         limit: usize,
+        sort: ArticleSort,
+        cursor: Option<PageCursor>,
+        viewer: Option<&UserName>,
     ) -> Result<Vec<Article>, ArticleServiceError> {
-        self.repository.get_articles(skip, limit).await
+        self.repository
+            .get_articles(skip, limit, sort, cursor, viewer)
+            .await
     }
 
     async fn get_article_by_id(&self, id: ArticleId) -> Result<Article, ArticleServiceError> {
         self.repository.get_article_by_id(id).await
     }
 
+    async fn get_article_by_slug(
+        &self,
+        author: &str,
+        slug: &str,
+    ) -> Result<Article, ArticleServiceError> {
+        self.repository.get_article_by_slug(author, slug).await
+    }
+
     async fn create_article(
         &self,
         title: String,
         author: UserName,
         content: String,
+        visibility: Visibility,
+        publish_at: Option<DateTime<Utc>>,
+        tags: Vec<String>,
     ) -> Result<Article, ArticleServiceError> {
-        self.repository.add_article(title, author, content).await
+        // 予約投稿は下書きにのみ設定できる
+        if publish_at.is_some() && visibility != Visibility::Draft {
+            return Err(ArticleServiceError::InvalidSchedule);
+        }
+        let tags = normalize_tags(tags);
+        // スラッグに記事のIDを使用する場合があるため、追加する前にIDを決める
+        let id = ArticleId::new();
+        // スラッグの確認と追加の間に他の記事が同じスラッグを使用した場合は、スラッグを作り直して再試行する
+        for _ in 0..MAX_SLUG_ATTEMPTS {
+            let slug = self.unique_slug(author.as_str(), &title, id).await?;
+            match self
+                .repository
+                .add_article(
+                    id,
+                    title.clone(),
+                    slug,
+                    author.clone(),
+                    content.clone(),
+                    visibility,
+                    publish_at,
+                    tags.clone(),
+                )
+                .await
+            {
+                Err(ArticleServiceError::ArticleAlreadyExists) => continue,
+                result => return result,
+            }
+        }
+        Err(ArticleServiceError::ArticleAlreadyExists)
     }
 
     async fn update_article(
@@ -49,22 +186,249 @@ impl<A: ArticleRepository + Clone + Send + Sync> ArticleService for ArticleUseca
         id: ArticleId,
         title: Option<String>,
         content: Option<String>,
+        visibility: Option<Visibility>,
+        publish_at: Option<Option<DateTime<Utc>>>,
+        tags: Option<Vec<String>>,
+        expected_version: Option<u32>,
     ) -> Result<Article, ArticleServiceError> {
-        self.repository.update_article(id, title, content).await
+        let current = self.repository.get_article_by_id(id).await?;
+        let new_visibility = visibility.unwrap_or(current.visibility);
+        let publish_at = if new_visibility == Visibility::Draft {
+            publish_at
+        } else if matches!(publish_at, Some(Some(_))) {
+            return Err(ArticleServiceError::InvalidSchedule);
+        } else if current.publish_at.is_some() {
+            // 下書き以外に変更された記事の予約は取り消す
+            Some(None)
+        } else {
+            publish_at
+        };
+        self.update_with_slug(
+            &current,
+            title,
+            content,
+            visibility,
+            publish_at,
+            tags.map(normalize_tags),
+            expected_version,
+        )
+        .await
     }
 
-    async fn delete_article(&self, id: ArticleId) -> Result<(), ArticleServiceError> {
-        self.repository.delete_article(id).await
+    async fn delete_article(
+        &self,
+        id: ArticleId,
+        expected_version: Option<u32>,
+    ) -> Result<(), ArticleServiceError> {
+        self.repository.delete_article(id, expected_version).await
     }
 
     async fn search_articles(
         &self,
         skip: usize,
         limit: usize,
-        query: ArticleQuery,
+        mut query: ArticleQuery,
+        viewer: Option<&UserName>,
     ) -> Result<Vec<Article>, ArticleServiceError> {
+        query.tags = normalize_tags(query.tags);
+        query.any_tags = normalize_tags(query.any_tags);
         self.repository
-            .get_articles_with_query(skip, limit, query)
+            .get_articles_with_query(skip, limit, query, viewer)
             .await
     }
+
+    async fn search_full_text(
+        &self,
+        q: &str,
+        mut query: ArticleQuery,
+        skip: usize,
+        limit: usize,
+        viewer: Option<&UserName>,
+    ) -> Result<SearchResults, ArticleServiceError> {
+        let terms = query_terms(q);
+        if terms.is_empty() {
+            return Ok(SearchResults {
+                hits: Vec::new(),
+                total: 0,
+            });
+        }
+        query.tags = normalize_tags(query.tags);
+        query.any_tags = normalize_tags(query.any_tags);
+        let total = self
+            .repository
+            .count_articles_by_terms(&terms, query.clone(), viewer)
+            .await?;
+        let hits = self
+            .repository
+            .search_articles_by_terms(q, &terms, query, skip, limit, viewer)
+            .await?
+            .into_iter()
+            .map(|(score, article)| SearchHit {
+                snippet: snippet(&article.content, &terms),
+                score,
+                article,
+            })
+            .collect();
+        Ok(SearchResults { hits, total })
+    }
+
+    async fn count_articles(&self, viewer: Option<&UserName>) -> Result<u64, ArticleServiceError> {
+        self.repository.count_articles(viewer).await
+    }
+
+    async fn get_articles_by_author(
+        &self,
+        author: &str,
+        skip: usize,
+        limit: usize,
+        sort: ArticleSort,
+        cursor: Option<PageCursor>,
+        viewer: Option<&UserName>,
+    ) -> Result<Vec<Article>, ArticleServiceError> {
+        self.repository
+            .get_articles_by_author(author, skip, limit, sort, cursor, viewer)
+            .await
+    }
+
+    async fn count_articles_by_author(
+        &self,
+        author: &str,
+        viewer: Option<&UserName>,
+    ) -> Result<u64, ArticleServiceError> {
+        self.repository
+            .count_articles_by_author(author, viewer)
+            .await
+    }
+
+    async fn get_author_stats(
+        &self,
+        author: &str,
+        viewer: Option<&UserName>,
+    ) -> Result<AuthorArticleStats, ArticleServiceError> {
+        self.repository.get_author_stats(author, viewer).await
+    }
+
+    async fn count_search_articles(
+        &self,
+        mut query: ArticleQuery,
+        viewer: Option<&UserName>,
+    ) -> Result<u64, ArticleServiceError> {
+        query.tags = normalize_tags(query.tags);
+        query.any_tags = normalize_tags(query.any_tags);
+        self.repository
+            .count_articles_with_query(query, viewer)
+            .await
+    }
+
+    async fn get_drafts(
+        &self,
+        author: &UserName,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Vec<Article>, ArticleServiceError> {
+        self.repository.get_drafts(author, skip, limit).await
+    }
+
+    async fn publish_due_articles(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Article>, ArticleServiceError> {
+        self.repository.publish_due_articles(now).await
+    }
+
+    async fn get_revisions(
+        &self,
+        id: ArticleId,
+    ) -> Result<Vec<ArticleRevision>, ArticleServiceError> {
+        self.repository.get_revisions(id).await
+    }
+
+    async fn get_revision(
+        &self,
+        id: ArticleId,
+        rev: u32,
+    ) -> Result<ArticleRevision, ArticleServiceError> {
+        self.repository.get_revision(id, rev).await
+    }
+
+    async fn diff_revisions(
+        &self,
+        id: ArticleId,
+        from: u32,
+        to: u32,
+    ) -> Result<RevisionDiff, ArticleServiceError> {
+        let from = self.repository.get_revision(id, from).await?;
+        let to = self.repository.get_revision(id, to).await?;
+        Ok(RevisionDiff::between(&from, &to))
+    }
+
+    async fn restore_revision(
+        &self,
+        id: ArticleId,
+        rev: u32,
+    ) -> Result<Article, ArticleServiceError> {
+        // 過去の版を上書きせず、その内容で新しい版を作成する
+        let revision = self.repository.get_revision(id, rev).await?;
+        let current = self.repository.get_article_by_id(id).await?;
+        self.update_with_slug(
+            &current,
+            Some(revision.title),
+            Some(revision.content),
+            None,
+            None,
+            None,
+            None,
+        )
+        .await
+    }
+
+    async fn get_tag_counts(&self) -> Result<Vec<TagCount>, ArticleServiceError> {
+        self.repository.get_tag_counts().await
+    }
+
+    async fn assign_missing_slugs(&self) -> Result<usize, ArticleServiceError> {
+        let articles = self.repository.get_articles_without_slug().await?;
+        let mut assigned = 0;
+        for article in articles {
+            let slug = self
+                .unique_slug(article.author.as_str(), &article.title, article.id)
+                .await?;
+            self.repository.assign_slug(article.id, slug).await?;
+            assigned += 1;
+        }
+        Ok(assigned)
+    }
+
+    async fn add_reaction(
+        &self,
+        id: ArticleId,
+        user: UserName,
+        kind: ReactionKind,
+    ) -> Result<Article, ArticleServiceError> {
+        self.repository.add_reaction(id, user, kind).await
+    }
+
+    async fn remove_reaction(
+        &self,
+        id: ArticleId,
+        user: &UserName,
+        kind: ReactionKind,
+    ) -> Result<Article, ArticleServiceError> {
+        self.repository.remove_reaction(id, user, kind).await
+    }
+
+    async fn get_user_reactions(
+        &self,
+        ids: &[ArticleId],
+        user: &UserName,
+    ) -> Result<HashMap<ArticleId, Vec<ReactionKind>>, ArticleServiceError> {
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        self.repository.get_user_reactions(ids, user).await
+    }
+
+    fn render_content_html(&self, article: &Article) -> Arc<str> {
+        self.html_cache.render(article)
+    }
 }
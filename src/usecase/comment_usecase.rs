@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+
+use crate::domain::{
+    models::{
+        article::ArticleId,
+        comment::{Comment, CommentId, CommentStatus, CommentThread},
+        comment_service::{CommentService, CommentServiceError},
+        user::CommentModeration,
+        user_name::UserName,
+    },
+    repositorys::comment_repository::CommentRepository,
+};
+
+#[derive(Clone)]
+pub struct CommentUsecase<C: CommentRepository + Clone> {
+    repository: C,
+}
+
+impl<C: CommentRepository + Clone> CommentUsecase<C> {
+    pub fn new(repository: C) -> Self {
+        CommentUsecase { repository }
+    }
+}
+
+/// 作成日時の昇順に並んだコメントを返信のスレッドに組み立てる
+/// 削除されたコメントは、返信が残っている場合のみスレッドに含める
+/// 閲覧できないコメントは、その返信も含めてスレッドから取り除く
+fn build_threads(
+    comments: Vec<Comment>,
+    is_visible: impl Fn(&Comment) -> bool,
+) -> Vec<CommentThread> {
+    let mut children: HashMap<Option<CommentId>, Vec<Comment>> = HashMap::new();
+    for comment in comments {
+        children.entry(comment.parent_id).or_default().push(comment);
+    }
+
+    fn build(
+        parent_id: Option<CommentId>,
+        children: &mut HashMap<Option<CommentId>, Vec<Comment>>,
+        is_visible: &dyn Fn(&Comment) -> bool,
+    ) -> Vec<CommentThread> {
+        let Some(comments) = children.remove(&parent_id) else {
+            return Vec::new();
+        };
+        comments
+            .into_iter()
+            .filter(|comment| is_visible(comment))
+            .filter_map(|comment| {
+                let replies = build(Some(comment.id), children, is_visible);
+                if comment.deleted && replies.is_empty() {
+                    None
+                } else {
+                    Some(CommentThread { comment, replies })
+                }
+            })
+            .collect()
+    }
+
+    build(None, &mut children, &is_visible)
+}
+
+#[async_trait]
+impl<C: CommentRepository + Clone + Send + Sync> CommentService for CommentUsecase<C> {
+    async fn get_comments(
+        &self,
+        article_id: ArticleId,
+        article_author: &UserName,
+        viewer: Option<&UserName>,
+    ) -> Result<Vec<CommentThread>, CommentServiceError> {
+        let comments = self.repository.get_comments_by_article(article_id).await?;
+        Ok(build_threads(comments, |comment| {
+            comment.is_visible_to(viewer, article_author)
+        }))
+    }
+
+    async fn get_comment_by_id(&self, id: CommentId) -> Result<Comment, CommentServiceError> {
+        let comment = self.repository.get_comment_by_id(id).await?;
+        // 削除されたコメントは、スレッドの一部としてのみ扱う
+        if comment.deleted {
+            return Err(CommentServiceError::CommentNotFound);
+        }
+        Ok(comment)
+    }
+
+    async fn create_comment(
+        &self,
+        article_id: ArticleId,
+        article_author: UserName,
+        parent_id: Option<CommentId>,
+        author: UserName,
+        content: String,
+        moderation: CommentModeration,
+    ) -> Result<Comment, CommentServiceError> {
+        if moderation == CommentModeration::Disabled {
+            return Err(CommentServiceError::CommentsDisabled);
+        }
+        if content.trim().is_empty() {
+            return Err(CommentServiceError::EmptyContent);
+        }
+        // 返信先は同じ記事の、削除されておらず閲覧できるコメントに限る
+        if let Some(parent_id) = parent_id {
+            match self.get_comment_by_id(parent_id).await {
+                Ok(parent)
+                    if parent.article_id == article_id
+                        && parent.is_visible_to(Some(&author), &article_author) => {}
+                Ok(_) | Err(CommentServiceError::CommentNotFound) => {
+                    return Err(CommentServiceError::ParentNotFound);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        // 記事の著者自身のコメントは承認を待たない
+        let status = if moderation == CommentModeration::Hold && author != article_author {
+            CommentStatus::Pending
+        } else {
+            CommentStatus::Approved
+        };
+        self.repository
+            .add_comment(
+                article_id,
+                article_author,
+                parent_id,
+                author,
+                content,
+                status,
+            )
+            .await
+    }
+
+    async fn update_comment(
+        &self,
+        id: CommentId,
+        content: String,
+    ) -> Result<Comment, CommentServiceError> {
+        if content.trim().is_empty() {
+            return Err(CommentServiceError::EmptyContent);
+        }
+        self.get_comment_by_id(id).await?;
+        self.repository.update_comment(id, content).await
+    }
+
+    async fn delete_comment(&self, id: CommentId) -> Result<(), CommentServiceError> {
+        self.get_comment_by_id(id).await?;
+        self.repository.delete_comment(id).await
+    }
+
+    async fn get_pending_comments(
+        &self,
+        article_author: &UserName,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Vec<Comment>, CommentServiceError> {
+        self.repository
+            .get_pending_comments(article_author, skip, limit)
+            .await
+    }
+
+    async fn set_comment_status(
+        &self,
+        id: CommentId,
+        status: CommentStatus,
+    ) -> Result<Comment, CommentServiceError> {
+        self.get_comment_by_id(id).await?;
+        self.repository.update_comment_status(id, status).await
+    }
+
+    async fn delete_comments_by_article(
+        &self,
+        article_id: ArticleId,
+    ) -> Result<(), CommentServiceError> {
+        self.repository.delete_comments_by_article(article_id).await
+    }
+
+    async fn count_comments_by_articles(
+        &self,
+        article_ids: &[ArticleId],
+    ) -> Result<u64, CommentServiceError> {
+        if article_ids.is_empty() {
+            return Ok(0);
+        }
+        self.repository
+            .count_comments_by_articles(article_ids)
+            .await
+    }
+}
@@ -0,0 +1,109 @@
+use async_trait::async_trait;
+
+use crate::domain::{
+    models::{
+        article::Article,
+        follow::Follow,
+        follow_service::{FollowService, FollowServiceError},
+        page_cursor::PageCursor,
+        user_name::UserName,
+    },
+    repositorys::{
+        article_repository::ArticleRepository, follow_repository::FollowRepository,
+        user_repository::UserRepository,
+    },
+};
+
+/// フォローとタイムラインのユースケース
+/// ユーザーの存在確認と記事の取得には、他のユースケースと同じリポジトリを共有する
+#[derive(Clone)]
+pub struct FollowUsecase<F, U, A>
+where
+    F: FollowRepository + Clone,
+    U: UserRepository + Clone,
+    A: ArticleRepository + Clone,
+{
+    repository: F,
+    user_repository: U,
+    article_repository: A,
+}
+
+impl<F, U, A> FollowUsecase<F, U, A>
+where
+    F: FollowRepository + Clone,
+    U: UserRepository + Clone,
+    A: ArticleRepository + Clone,
+{
+    pub fn new(repository: F, user_repository: U, article_repository: A) -> Self {
+        FollowUsecase {
+            repository,
+            user_repository,
+            article_repository,
+        }
+    }
+
+    async fn find_user_name(&self, name: &str) -> Result<UserName, FollowServiceError> {
+        Ok(self.user_repository.get_user_by_name(name).await?.name)
+    }
+}
+
+#[async_trait]
+impl<F, U, A> FollowService for FollowUsecase<F, U, A>
+where
+    F: FollowRepository + Clone + Send + Sync,
+    U: UserRepository + Clone + Send + Sync,
+    A: ArticleRepository + Clone + Send + Sync,
+{
+    async fn follow(&self, follower: &UserName, followee: &str) -> Result<(), FollowServiceError> {
+        let followee = self.find_user_name(followee).await?;
+        if &followee == follower {
+            return Err(FollowServiceError::CannotFollowSelf);
+        }
+        self.repository.add_follow(follower, &followee).await
+    }
+
+    async fn unfollow(
+        &self,
+        follower: &UserName,
+        followee: &str,
+    ) -> Result<(), FollowServiceError> {
+        let followee = self.find_user_name(followee).await?;
+        self.repository.remove_follow(follower, &followee).await
+    }
+
+    async fn get_followers(
+        &self,
+        user_name: &str,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Vec<Follow>, FollowServiceError> {
+        let user_name = self.find_user_name(user_name).await?;
+        self.repository.get_followers(&user_name, skip, limit).await
+    }
+
+    async fn get_following(
+        &self,
+        user_name: &str,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Vec<Follow>, FollowServiceError> {
+        let user_name = self.find_user_name(user_name).await?;
+        self.repository.get_following(&user_name, skip, limit).await
+    }
+
+    async fn get_timeline(
+        &self,
+        viewer: &UserName,
+        cursor: Option<PageCursor>,
+        limit: usize,
+    ) -> Result<Vec<Article>, FollowServiceError> {
+        let authors = self.repository.get_followee_names(viewer).await?;
+        if authors.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(self
+            .article_repository
+            .get_articles_by_authors(&authors, cursor, limit, Some(viewer))
+            .await?)
+    }
+}
@@ -1,2 +1,5 @@
 pub mod article_usecase;
+pub mod comment_usecase;
+pub mod follow_usecase;
+pub mod scheduled_publisher;
 pub mod user_usecase;
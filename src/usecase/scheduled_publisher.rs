@@ -0,0 +1,128 @@
+use std::time::Duration;
+
+use tokio::{task::JoinHandle, time::MissedTickBehavior};
+
+use crate::domain::models::{article_service::ArticleService, clock::Clock};
+
+/// 予約投稿の日時を過ぎた下書きを定期的に公開するタスクを起動する
+/// `interval`ごとに`clock`の現在時刻で`ArticleService::publish_due_articles`を呼び出す
+pub fn spawn_scheduled_publisher<A, C>(
+    article_service: A,
+    clock: C,
+    interval: Duration,
+) -> JoinHandle<()>
+where
+    A: ArticleService + Send + Sync + 'static,
+    C: Clock + 'static,
+{
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        // 処理が長引いた場合に溜まった分をまとめて実行しないようにする
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match article_service.publish_due_articles(clock.now()).await {
+                Ok(articles) => {
+                    for article in articles {
+                        tracing::info!(article_id = %article.id, "scheduled article published");
+                    }
+                }
+                Err(e) => tracing::warn!("Failed to publish scheduled articles: {e}"),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use chrono::{DateTime, TimeDelta, Utc};
+
+    use super::*;
+    use crate::{
+        domain::models::{article::Visibility, article_query::ArticleSort, user_name::UserName},
+        infrastructure::inmemory_article_repository::InMemoryArticleRepository,
+        usecase::article_usecase::ArticleUsecase,
+    };
+
+    /// テストから現在時刻を進められる`Clock`
+    #[derive(Clone)]
+    struct ManualClock(Arc<Mutex<DateTime<Utc>>>);
+
+    impl ManualClock {
+        fn advance(&self, delta: TimeDelta) {
+            *self.0.lock().unwrap() += delta;
+        }
+    }
+
+    impl Clock for ManualClock {
+        fn now(&self) -> DateTime<Utc> {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn draft_is_published_when_clock_passes_publish_at() {
+        const INTERVAL: Duration = Duration::from_secs(60);
+
+        let start = Utc::now();
+        let clock = ManualClock(Arc::new(Mutex::new(start)));
+        let article_service = ArticleUsecase::new(InMemoryArticleRepository::default());
+
+        let author = UserName::new("alice".to_string());
+        let scheduled = article_service
+            .create_article(
+                "予約投稿".to_string(),
+                author.clone(),
+                "1時間後に公開".to_string(),
+                Visibility::Draft,
+                Some(start + TimeDelta::hours(1)),
+                Vec::new(),
+            )
+            .await
+            .unwrap();
+        let later = article_service
+            .create_article(
+                "後の予約投稿".to_string(),
+                author.clone(),
+                "2時間後に公開".to_string(),
+                Visibility::Draft,
+                Some(start + TimeDelta::hours(2)),
+                Vec::new(),
+            )
+            .await
+            .unwrap();
+
+        let _publisher =
+            spawn_scheduled_publisher(article_service.clone(), clock.clone(), INTERVAL);
+
+        // 予約時刻より前は公開されない
+        tokio::time::sleep(INTERVAL * 2).await;
+        let article = article_service
+            .get_article_by_id(scheduled.id)
+            .await
+            .unwrap();
+        assert_eq!(article.visibility, Visibility::Draft);
+
+        // 予約時刻を過ぎた記事のみが公開される
+        clock.advance(TimeDelta::minutes(90));
+        tokio::time::sleep(INTERVAL).await;
+        let article = article_service
+            .get_article_by_id(scheduled.id)
+            .await
+            .unwrap();
+        assert_eq!(article.visibility, Visibility::Published);
+        assert_eq!(article.publish_at, None);
+        assert_eq!(article.updated_at, clock.now());
+        let article = article_service.get_article_by_id(later.id).await.unwrap();
+        assert_eq!(article.visibility, Visibility::Draft);
+
+        let listed = article_service
+            .get_articles(0, 10, ArticleSort::default(), None, None)
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, scheduled.id);
+    }
+}
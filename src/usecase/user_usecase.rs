@@ -1,30 +1,191 @@
 use async_trait::async_trait;
-use sha2::{Digest, Sha256};
 
 use crate::domain::{
     models::{
-        user::User,
+        article_service::ArticleServiceError,
+        page_cursor::PageCursor,
+        password_hasher::{PasswordHash, PasswordHasher},
+        slug::{numbered_slug, slugify},
+        user::{ArticleDeletionPolicy, CommentModeration, User, UserId, UserRole},
         user_name::UserName,
         user_service::{UserService, UserServiceError},
     },
-    repositorys::user_repository::UserRepository,
+    repositorys::{
+        article_repository::ArticleRepository, comment_repository::CommentRepository,
+        follow_repository::FollowRepository, user_repository::UserRepository,
+    },
 };
 
+/// ユーザーを削除するときに、ユーザーの記事やコメント、フォロー、リアクションも処理するため、
+/// それぞれのリポジトリを保持する
 #[derive(Clone)]
-pub struct UserUsecase<U: UserRepository + Clone> {
+pub struct UserUsecase<
+    U: UserRepository + Clone,
+    H: PasswordHasher,
+    A: ArticleRepository + Clone,
+    C: CommentRepository + Clone,
+    F: FollowRepository + Clone,
+> {
     repository: U,
+    hasher: H,
+    article_repository: A,
+    comment_repository: C,
+    follow_repository: F,
 }
 
-impl<U: UserRepository + Clone> UserUsecase<U> {
-    pub fn new(repository: U) -> Self {
-        UserUsecase { repository }
+impl<
+    U: UserRepository + Clone,
+    H: PasswordHasher,
+    A: ArticleRepository + Clone,
+    C: CommentRepository + Clone,
+    F: FollowRepository + Clone,
+> UserUsecase<U, H, A, C, F>
+{
+    /// `hasher`は新しく保存するパスワードのハッシュ化と、ログイン時の検証に使用される
+    pub fn new(
+        repository: U,
+        hasher: H,
+        article_repository: A,
+        comment_repository: C,
+        follow_repository: F,
+    ) -> Self {
+        UserUsecase {
+            repository,
+            hasher,
+            article_repository,
+            comment_repository,
+            follow_repository,
+        }
+    }
+
+    /// ユーザーの記事とコメント、フォロー、リアクションを処理する
+    /// `Reject`の場合は記事がないことを確認済みのため、残っている記事は`Reassign`と同じく引き継ぐ
+    ///
+    /// 各処理は1つずつ行われ、全体として不可分ではない　途中で失敗した場合は一部のみが処理された状態になるが、
+    /// 処理済みのものは再び対象にならないか、同じ結果になるため、もう一度呼び出して続きを処理できる
+    async fn release_content(
+        &self,
+        name: &UserName,
+        articles: ArticleDeletionPolicy,
+    ) -> Result<(), UserServiceError> {
+        let tombstone = UserName::deleted();
+        match articles {
+            ArticleDeletionPolicy::Cascade => {
+                for article in self
+                    .article_repository
+                    .get_all_articles_by_author(name)
+                    .await?
+                {
+                    // 途中で失敗しても再試行で記事から辿れるように、コメントを先に削除する
+                    self.comment_repository
+                        .delete_comments_by_article(article.id)
+                        .await?;
+                    match self
+                        .article_repository
+                        .delete_article(article.id, None)
+                        .await
+                    {
+                        // 並行して削除された記事は削除済みとして扱う
+                        Ok(()) | Err(ArticleServiceError::ArticleNotFound) => {}
+                        Err(e) => return Err(e.into()),
+                    }
+                }
+                self.comment_repository
+                    .delete_comments_by_author(name)
+                    .await?;
+            }
+            ArticleDeletionPolicy::Reassign | ArticleDeletionPolicy::Reject => {
+                self.reassign_articles(name, &tombstone).await?;
+                self.comment_repository
+                    .reassign_article_author(name, &tombstone)
+                    .await?;
+            }
+        }
+        // 返信のスレッドを保つため、コメントは残して著者のみを変更する
+        self.comment_repository
+            .reassign_comment_author(name, &tombstone)
+            .await?;
+        self.follow_repository.remove_follows_of(name).await?;
+        // 1つずつ取り消して、記事のリアクションの数を不可分に減らす
+        for (id, kind) in self.article_repository.get_reactions_by_user(name).await? {
+            match self
+                .article_repository
+                .remove_reaction(id, name, kind)
+                .await
+            {
+                Ok(_) | Err(ArticleServiceError::ArticleNotFound) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(())
+    }
+
+    /// ユーザーが記事（下書きや非公開の記事を含む）を1つでも持つかどうか
+    async fn has_articles(&self, name: &UserName) -> Result<bool, UserServiceError> {
+        Ok(self
+            .article_repository
+            .count_all_articles_by_author(name)
+            .await?
+            > 0)
+    }
+
+    /// 著者のすべての記事の著者を`to`に変更する
+    /// スラッグが`to`の記事と重複する場合は番号を付け直す
+    async fn reassign_articles(
+        &self,
+        from: &UserName,
+        to: &UserName,
+    ) -> Result<(), UserServiceError> {
+        for article in self
+            .article_repository
+            .get_all_articles_by_author(from)
+            .await?
+        {
+            let base = if article.slug.is_empty() {
+                slugify(&article.title, article.id)
+            } else {
+                article.slug.clone()
+            };
+            // 重複はリポジトリが一意性を確認して返すため、重複しなくなるまで番号を進める
+            for n in 1.. {
+                match self
+                    .article_repository
+                    .reassign_article(article.id, to.clone(), numbered_slug(&base, n))
+                    .await
+                {
+                    Ok(_) | Err(ArticleServiceError::ArticleNotFound) => break,
+                    Err(ArticleServiceError::ArticleAlreadyExists) => continue,
+                    Err(e) => return Err(e.into()),
+                }
+            }
+        }
+        Ok(())
     }
 }
 
 #[async_trait]
-impl<U: UserRepository + Clone + Send + Sync> UserService for UserUsecase<U> {
-    async fn get_users(&self, skip: usize, limit: usize) -> Result<Vec<User>, UserServiceError> {
-        self.repository.get_users(skip, limit).await
+impl<
+    U: UserRepository + Clone + Send + Sync,
+    H: PasswordHasher,
+    A: ArticleRepository + Clone + Send + Sync,
+    C: CommentRepository + Clone + Send + Sync,
+    F: FollowRepository + Clone + Send + Sync,
+> UserService for UserUsecase<U, H, A, C, F>
+{
+    async fn get_users(
+        &self,
+        skip: usize,
+        limit: usize,
+        cursor: Option<PageCursor>,
+    ) -> Result<Vec<User>, UserServiceError> {
+        self.repository.get_users(skip, limit, cursor).await
+    }
+    async fn count_users(&self) -> Result<u64, UserServiceError> {
+        self.repository.count_users().await
+    }
+
+    async fn get_user_by_id(&self, id: UserId) -> Result<User, UserServiceError> {
+        self.repository.get_user_by_id(id).await
     }
 
     async fn get_user_by_name(&self, name: &str) -> Result<User, UserServiceError> {
@@ -40,15 +201,9 @@ impl<U: UserRepository + Clone + Send + Sync> UserService for UserUsecase<U> {
         show_email: bool,
         password: String,
     ) -> Result<User, UserServiceError> {
+        let pw_hash = PasswordHash::Phc(self.hasher.hash(&password)?);
         self.repository
-            .add_user(
-                name,
-                display_name,
-                intro,
-                email,
-                show_email,
-                Sha256::digest(password.as_bytes()).to_vec(),
-            )
+            .add_user(name, display_name, intro, email, show_email, pw_hash)
             .await
     }
 
@@ -60,8 +215,13 @@ impl<U: UserRepository + Clone + Send + Sync> UserService for UserUsecase<U> {
         email: Option<String>,
         show_email: Option<bool>,
         password: Option<String>,
+        comment_moderation: Option<CommentModeration>,
+        expected_version: Option<u32>,
     ) -> Result<User, UserServiceError> {
         let user = self.repository.get_user_by_name(&name).await?;
+        let pw_hash = password
+            .map(|pw| self.hasher.hash(&pw).map(PasswordHash::Phc))
+            .transpose()?;
         self.repository
             .update_user(
                 user.id,
@@ -70,17 +230,233 @@ impl<U: UserRepository + Clone + Send + Sync> UserService for UserUsecase<U> {
                 intro,
                 email,
                 show_email,
-                password.map(|pw| Sha256::digest(pw.as_bytes()).to_vec()),
+                pw_hash,
+                comment_moderation,
+                expected_version,
             )
             .await
     }
 
-    async fn delete_user(&self, name: &str) -> Result<(), UserServiceError> {
+    async fn set_role(&self, name: &str, role: UserRole) -> Result<User, UserServiceError> {
         let user = self.repository.get_user_by_name(name).await?;
-        self.repository.delete_user(user.id).await
+        self.repository.update_role(user.id, role).await
+    }
+
+    async fn delete_user(
+        &self,
+        name: &str,
+        expected_version: Option<u32>,
+        articles: ArticleDeletionPolicy,
+    ) -> Result<(), UserServiceError> {
+        let user = self.repository.get_user_by_name(name).await?;
+        // 記事などを処理した後に削除が失敗しないように、先にバージョンを確認する
+        if expected_version.is_some_and(|version| version != user.version) {
+            return Err(UserServiceError::VersionMismatch);
+        }
+        if articles == ArticleDeletionPolicy::Reject && self.has_articles(&user.name).await? {
+            return Err(UserServiceError::HasArticles);
+        }
+
+        // 途中で失敗した場合は、処理済みの内容を残したまま、再試行できることを示すエラーを返す
+        self.release_content(&user.name, articles)
+            .await
+            .map_err(|e| UserServiceError::DeletionIncomplete(Box::new(e)))?;
+        // 処理している間に作成された記事は処理されていないため、削除する直前にもう一度確認する
+        // `Reject`の記事を引き継いだり、他の方法の記事を残したりしないように、削除せずに`HasArticles`を返す
+        // 確認してから削除するまでの間に作成された記事は、作成時に著者が存在することを確認して取り除かれる
+        if self.has_articles(&user.name).await? {
+            return Err(UserServiceError::HasArticles);
+        }
+        self.repository.delete_user(user.id, expected_version).await
     }
 
     async fn validate_user_name(&self, name: &str) -> Result<UserName, UserServiceError> {
         self.repository.validate_user_name(name).await
     }
+
+    async fn authenticate(
+        &self,
+        name: &str,
+        password: &str,
+    ) -> Result<Option<User>, UserServiceError> {
+        let user = match self.repository.get_user_by_name(name).await {
+            Ok(user) => user,
+            Err(UserServiceError::UserNotFound) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        if !user.pw_hash.verify(&self.hasher, password) {
+            return Ok(None);
+        }
+        if !user.pw_hash.needs_rehash(&self.hasher) {
+            return Ok(Some(user));
+        }
+
+        // 旧形式や古いパラメータのハッシュは、平文のパスワードが手元にあるログイン時に移行する
+        // 移行に失敗してもログイン自体は成功させる
+        let rehashed = match self.hasher.hash(password) {
+            Ok(phc) => {
+                self.repository
+                    .update_user(
+                        user.id,
+                        None,
+                        None,
+                        None,
+                        None,
+                        None,
+                        Some(PasswordHash::Phc(phc)),
+                        None,
+                        None,
+                    )
+                    .await
+            }
+            Err(e) => Err(e.into()),
+        };
+        match rehashed {
+            Ok(user) => Ok(Some(user)),
+            Err(e) => {
+                tracing::warn!("Failed to rehash password of {}: {e}", user.name);
+                Ok(Some(user))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sha2::{Digest, Sha256};
+
+    use super::*;
+    use crate::domain::models::article::{ArticleId, Visibility};
+    use crate::infrastructure::{
+        argon2_password_hasher::Argon2PasswordHasher,
+        inmemory_article_repository::InMemoryArticleRepository,
+        inmemory_comment_repository::InMemoryCommentRepository,
+        inmemory_follow_repository::InMemoryFollowRepository,
+        inmemory_user_repository::InMemoryUserRepository,
+    };
+
+    #[tokio::test]
+    async fn legacy_hash_is_upgraded_on_login() {
+        let repository = InMemoryUserRepository::default();
+        let legacy = PasswordHash::LegacySha256(Sha256::digest(b"password123").to_vec());
+        repository
+            .add_user(
+                "furakuta".to_string(),
+                "ふらくた".to_string(),
+                String::new(),
+                "furakuta@example.com".to_string(),
+                false,
+                legacy.clone(),
+            )
+            .await
+            .unwrap();
+        let usecase = UserUsecase::new(
+            repository.clone(),
+            Argon2PasswordHasher::default(),
+            InMemoryArticleRepository::default(),
+            InMemoryCommentRepository::default(),
+            InMemoryFollowRepository::default(),
+        );
+
+        // 間違ったパスワードでは移行されない
+        assert!(
+            usecase
+                .authenticate("furakuta", "wrong")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        let user = repository.get_user_by_name("furakuta").await.unwrap();
+        assert_eq!(user.pw_hash, legacy);
+
+        // 正しいパスワードでログインするとPHC形式に移行される
+        let user = usecase
+            .authenticate("furakuta", "password123")
+            .await
+            .unwrap()
+            .unwrap();
+        let PasswordHash::Phc(phc) = &user.pw_hash else {
+            panic!("password hash was not upgraded");
+        };
+        assert!(phc.starts_with("$argon2id$"));
+        let stored = repository.get_user_by_name("furakuta").await.unwrap();
+        assert_eq!(stored.pw_hash, user.pw_hash);
+
+        // 移行後のハッシュでもログインでき、再度移行はされない
+        let again = usecase
+            .authenticate("furakuta", "password123")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(again.pw_hash, user.pw_hash);
+    }
+
+    #[tokio::test]
+    async fn releasing_content_can_be_retried() {
+        let repository = InMemoryUserRepository::default();
+        let article_repository = InMemoryArticleRepository::default();
+        let comment_repository = InMemoryCommentRepository::default();
+        let usecase = UserUsecase::new(
+            repository.clone(),
+            Argon2PasswordHasher::default(),
+            article_repository.clone(),
+            comment_repository.clone(),
+            InMemoryFollowRepository::default(),
+        );
+        usecase
+            .create_user(
+                "alice".to_string(),
+                "Alice".to_string(),
+                String::new(),
+                "alice@example.com".to_string(),
+                false,
+                "password123".to_string(),
+            )
+            .await
+            .unwrap();
+        let alice = UserName::new("alice".to_string());
+        let article = article_repository
+            .add_article(
+                ArticleId::new(),
+                "記事".to_string(),
+                "article".to_string(),
+                alice.clone(),
+                "本文".to_string(),
+                Visibility::Published,
+                None,
+                Vec::new(),
+            )
+            .await
+            .unwrap();
+
+        // 記事がある場合は何も処理せずに拒否する
+        assert!(matches!(
+            usecase
+                .delete_user("alice", None, ArticleDeletionPolicy::Reject)
+                .await,
+            Err(UserServiceError::HasArticles)
+        ));
+        assert!(repository.get_user_by_name("alice").await.is_ok());
+
+        // 途中で失敗した場合と同じく、処理済みの内容があっても続きから処理できる
+        for _ in 0..2 {
+            usecase
+                .release_content(&alice, ArticleDeletionPolicy::Reassign)
+                .await
+                .unwrap();
+        }
+        let reassigned = article_repository
+            .get_article_by_id(article.id)
+            .await
+            .unwrap();
+        assert_eq!(reassigned.author, UserName::deleted());
+        usecase
+            .delete_user("alice", None, ArticleDeletionPolicy::Reject)
+            .await
+            .unwrap();
+        assert!(matches!(
+            repository.get_user_by_name("alice").await,
+            Err(UserServiceError::UserNotFound)
+        ));
+    }
 }